@@ -0,0 +1,258 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::{GameEvent, Subscriber};
+
+// Waves to clear in one game for Achievement::Veteran, the way to wave 10
+const VETERAN_WAVES: u32 = 9;
+// Score to reach in one game for Achievement::HighScorer
+const HIGH_SCORER_POINTS: u64 = 10000;
+
+/*
+ * Goals to reach while playing. There are no bunkers in the game yet, so
+ * instead of keeping them whole a wave has to be cleared without getting
+ * hit.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Achievement {
+   // Clear a wave without a shot that hits nothing
+   Sharpshooter,
+   // Clear a wave without getting hit
+   Untouchable,
+   // Destroy a ufo with the first shot fired after it showed up
+   Bullseye,
+   // Clear enough waves in one game to get from the first wave to the tenth,
+   // starting later doesn't get there any quicker
+   Veteran,
+   HighScorer
+}
+
+impl Achievement {
+   pub const ALL: [Achievement; 5] = [
+      Achievement::Sharpshooter,
+      Achievement::Untouchable,
+      Achievement::Bullseye,
+      Achievement::Veteran,
+      Achievement::HighScorer
+   ];
+
+   // How the achievement is stored in the achievements file
+   pub fn id(&self) -> &'static str {
+      match self {
+         Achievement::Sharpshooter => "sharpshooter",
+         Achievement::Untouchable => "untouchable",
+         Achievement::Bullseye => "bullseye",
+         Achievement::Veteran => "veteran",
+         Achievement::HighScorer => "high_scorer"
+      }
+   }
+
+   pub fn name(&self) -> &'static str {
+      match self {
+         Achievement::Sharpshooter => "SHARPSHOOTER",
+         Achievement::Untouchable => "UNTOUCHABLE",
+         Achievement::Bullseye => "BULLSEYE",
+         Achievement::Veteran => "VETERAN",
+         Achievement::HighScorer => "HIGH  SCORER"
+      }
+   }
+
+   pub fn description(&self) -> String {
+      match self {
+         Achievement::Sharpshooter => String::from("CLEAR  A  WAVE  WITHOUT  MISSING"),
+         Achievement::Untouchable => String::from("CLEAR  A  WAVE  WITHOUT  GETTING  HIT"),
+         Achievement::Bullseye => String::from("DESTROY  A  UFO  WITH  THE  FIRST  SHOT"),
+         Achievement::Veteran => format!("CLEAR  {}  WAVES  IN  ONE  GAME", VETERAN_WAVES),
+         Achievement::HighScorer => format!("SCORE  {}  IN  ONE  GAME", HIGH_SCORER_POINTS)
+      }
+   }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct AchievementsFile {
+   // Ids that aren't known (any more) are kept as they are
+   unlocked: Vec<String>
+}
+
+/*
+ * How far the current game and wave are towards the achievements.
+ */
+#[derive(Default)]
+struct Progress {
+   score: u64,
+   waves_cleared: u32,
+   wave_shots: u64,
+   wave_hits: u64,
+   wave_player_hit: bool,
+   // Shots fired since the ufo in the air showed up
+   ufo_shots: Option<u64>
+}
+
+/*
+ * The achievements unlocked so far, kept in a TOML file next to the high
+ * scores. Unlocked from the game events, the ones unlocked since the last
+ * call to take_unlocked are shown as toasts.
+ */
+pub struct Achievements {
+   unlocked: Vec<String>,
+   newly_unlocked: Vec<Achievement>,
+   progress: Progress,
+   path: PathBuf
+}
+
+impl Achievements {
+   pub fn load(path: &Path) -> Self {
+      let file = if path.exists() {
+         match fs::read_to_string(path).map_err(|err| err.to_string())
+                                       .and_then(|contents| toml::from_str::<AchievementsFile>(&contents).map_err(|err| err.to_string())) {
+            Err(err) => {
+               eprintln!("Failed to load achievements from {}, starting over: {}", path.display(), err);
+               AchievementsFile::default()
+            },
+            Ok(file) => file
+         }
+      } else {
+         AchievementsFile::default()
+      };
+
+      Achievements {
+         unlocked: file.unlocked,
+         newly_unlocked: Vec::new(),
+         progress: Progress::default(),
+         path: PathBuf::from(path)
+      }
+   }
+
+   pub fn save(&self) {
+      let file = AchievementsFile { unlocked: self.unlocked.clone() };
+
+      let contents = match toml::to_string_pretty(&file) {
+         Err(err) => {
+            eprintln!("Failed to serialize achievements: {}", err);
+            return;
+         },
+         Ok(contents) => contents
+      };
+
+      if let Err(err) = fs::write(&self.path, contents) {
+         eprintln!("Failed to write achievements to {}: {}", self.path.display(), err);
+      }
+   }
+
+   pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+      self.unlocked.iter().any(|id| id == achievement.id())
+   }
+
+   /*
+    * The achievements unlocked since the last call.
+    */
+   pub fn take_unlocked(&mut self) -> Vec<Achievement> {
+      std::mem::take(&mut self.newly_unlocked)
+   }
+
+   fn unlock(&mut self, achievement: Achievement) {
+      if self.is_unlocked(achievement) {
+         return;
+      }
+
+      self.unlocked.push(String::from(achievement.id()));
+      self.newly_unlocked.push(achievement);
+      self.save();
+   }
+
+   fn score(&mut self, points: u64) {
+      self.progress.score += points;
+
+      if self.progress.score >= HIGH_SCORER_POINTS {
+         self.unlock(Achievement::HighScorer);
+      }
+   }
+}
+
+impl Subscriber for Achievements {
+   fn notify(&mut self, _tick: u64, event: &GameEvent) {
+      match *event {
+         GameEvent::GameStarted { .. } => self.progress = Progress::default(),
+         GameEvent::ShotFired => {
+            self.progress.wave_shots += 1;
+
+            if let Some(shots) = self.progress.ufo_shots.as_mut() {
+               *shots += 1;
+            }
+         },
+         GameEvent::InvaderKilled { points, .. } => {
+            self.progress.wave_hits += 1;
+            self.score(points);
+         },
+         GameEvent::PlayerHit => self.progress.wave_player_hit = true,
+         GameEvent::WaveCleared { .. } => {
+            let progress = std::mem::take(&mut self.progress);
+            self.progress.score = progress.score;
+            self.progress.waves_cleared = progress.waves_cleared + 1;
+
+            if progress.wave_hits > 0 && progress.wave_shots <= progress.wave_hits {
+               self.unlock(Achievement::Sharpshooter);
+            }
+
+            if !progress.wave_player_hit {
+               self.unlock(Achievement::Untouchable);
+            }
+
+            if self.progress.waves_cleared >= VETERAN_WAVES {
+               self.unlock(Achievement::Veteran);
+            }
+         },
+         GameEvent::UfoSpawned => self.progress.ufo_shots = Some(0),
+         GameEvent::UfoKilled { points } => {
+            self.progress.wave_hits += 1;
+
+            // The shot may have been in the air before the ufo showed up
+            if self.progress.ufo_shots.is_some_and(|shots| shots <= 1) {
+               self.unlock(Achievement::Bullseye);
+            }
+
+            self.progress.ufo_shots = None;
+            self.score(points);
+         },
+         GameEvent::UfoLeft => self.progress.ufo_shots = None,
+         GameEvent::LifeLost { .. } | GameEvent::ExtraLife | GameEvent::GameOver { .. } => {}
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn clear_waves(achievements: &mut Achievements, start_wave: u32, waves: u32) {
+      achievements.notify(0, &GameEvent::GameStarted { wave: start_wave });
+
+      for wave in start_wave..start_wave + waves {
+         // Get hit every wave so clearing it doesn't unlock Untouchable too
+         achievements.notify(0, &GameEvent::PlayerHit);
+         achievements.notify(0, &GameEvent::WaveCleared { wave });
+      }
+   }
+
+   #[test]
+   fn veteran_takes_nine_waves_in_one_game() {
+      let path = std::env::temp_dir().join(format!("space_invaders_achievements_{}.toml", std::process::id()));
+      let mut achievements = Achievements::load(&path);
+
+      clear_waves(&mut achievements, 9, 1);
+      clear_waves(&mut achievements, 20, VETERAN_WAVES - 1);
+      assert!(!achievements.is_unlocked(Achievement::Veteran));
+      assert!(achievements.take_unlocked().is_empty());
+
+      clear_waves(&mut achievements, 1, VETERAN_WAVES);
+      assert!(achievements.is_unlocked(Achievement::Veteran));
+      assert_eq!(achievements.take_unlocked(), vec![Achievement::Veteran]);
+
+      let saved = Achievements::load(&path);
+      fs::remove_file(&path).unwrap();
+      assert!(saved.is_unlocked(Achievement::Veteran));
+   }
+}
@@ -0,0 +1,364 @@
+mod synth;
+
+use std::collections::HashMap;
+
+use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
+use rodio::buffer::SamplesBuffer;
+use serde::{Deserialize, Serialize};
+
+use crate::events::{GameEvent, Subscriber};
+
+pub const VOLUME_STEP: u8 = 10;
+
+/*
+ * Volumes in percent, stored in the settings file as
+ *
+ *    [audio]
+ *    master_volume = 100
+ *    sfx_volume = 80
+ *    music_volume = 80
+ *    muted = false
+ */
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+   pub master_volume: u8,
+   pub sfx_volume: u8,
+   pub music_volume: u8,
+   pub muted: bool
+}
+
+impl Default for AudioSettings {
+   fn default() -> Self {
+      AudioSettings {
+         master_volume: 100,
+         sfx_volume: 80,
+         music_volume: 80,
+         muted: false
+      }
+   }
+}
+
+impl AudioSettings {
+   /*
+    * Volume of a channel from 0 to 1, taking master volume and mute into account.
+    */
+   pub fn volume(&self, channel: Channel) -> f32 {
+      if self.muted {
+         return 0.0;
+      }
+
+      let channel_volume = match channel {
+         Channel::Sfx => self.sfx_volume,
+         Channel::Music => self.music_volume
+      };
+
+      (self.master_volume.min(100) as f32 / 100.0) * (channel_volume.min(100) as f32 / 100.0)
+   }
+}
+
+/*
+ * Changes a volume by steps of VOLUME_STEP, staying between 0 and 100.
+ */
+pub fn step_volume(volume: u8, direction: i8) -> u8 {
+   if direction < 0 {
+      volume.saturating_sub(VOLUME_STEP)
+   } else if direction > 0 {
+      volume.saturating_add(VOLUME_STEP).min(100)
+   } else {
+      volume
+   }
+}
+
+/*
+ * Sounds are mixed in two groups with their own volume. The march is the
+ * game's music, everything else is an effect.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Channel {
+   Sfx,
+   Music
+}
+
+impl Channel {
+   fn index(&self) -> usize {
+      *self as usize
+   }
+}
+
+/*
+ * Every sound the game makes. They are all synthesized when the game starts,
+ * there are no audio files.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sound {
+   PlayerShot,
+   InvaderKilled,
+   PlayerExplosion,
+   Ufo,
+   ExtraLife,
+   March1,
+   March2,
+   March3,
+   March4
+}
+
+impl Sound {
+   pub const ALL: [Sound; 9] = [
+      Sound::PlayerShot,
+      Sound::InvaderKilled,
+      Sound::PlayerExplosion,
+      Sound::Ufo,
+      Sound::ExtraLife,
+      Sound::March1,
+      Sound::March2,
+      Sound::March3,
+      Sound::March4
+   ];
+
+   const MARCH: [Sound; 4] = [Sound::March1, Sound::March2, Sound::March3, Sound::March4];
+
+   pub fn channel(&self) -> Channel {
+      match self {
+         Sound::March1 | Sound::March2 | Sound::March3 | Sound::March4 => Channel::Music,
+         _ => Channel::Sfx
+      }
+   }
+
+   fn samples(&self) -> Vec<f32> {
+      match self {
+         Sound::PlayerShot => synth::player_shot(),
+         Sound::InvaderKilled => synth::invader_killed(),
+         Sound::PlayerExplosion => synth::player_explosion(),
+         Sound::Ufo => synth::ufo_loop(),
+         Sound::ExtraLife => synth::extra_life(),
+         Sound::March1 => synth::march_note(0),
+         Sound::March2 => synth::march_note(1),
+         Sound::March3 => synth::march_note(2),
+         Sound::March4 => synth::march_note(3)
+      }
+   }
+}
+
+/*
+ * Something that can make noise. The game talks to a backend only through
+ * Audio, which picks a real one or a silent one.
+ */
+pub trait AudioBackend {
+   fn play(&mut self, sound: Sound);
+   fn start_loop(&mut self, sound: Sound);
+   fn stop_loop(&mut self, sound: Sound);
+   fn set_paused(&mut self, paused: bool);
+   // volume goes from 0 to 1 and applies to sounds already playing too
+   fn set_volume(&mut self, channel: Channel, volume: f32);
+}
+
+/*
+ * Used when there is no audio device (e.g. on CI) or no sound is wanted.
+ */
+pub struct NullBackend;
+
+impl AudioBackend for NullBackend {
+   fn play(&mut self, _sound: Sound) {}
+   fn start_loop(&mut self, _sound: Sound) {}
+   fn stop_loop(&mut self, _sound: Sound) {}
+   fn set_paused(&mut self, _paused: bool) {}
+   fn set_volume(&mut self, _channel: Channel, _volume: f32) {}
+}
+
+pub struct RodioBackend {
+   // Sound stops when the stream is dropped, so it's kept around
+   _stream: OutputStream,
+   handle: OutputStreamHandle,
+   samples: HashMap<Sound, Vec<f32>>,
+   loops: HashMap<Sound, Sink>,
+   volumes: [f32; 2],
+   paused: bool
+}
+
+impl RodioBackend {
+   pub fn new() -> Result<Self, String> {
+      let (stream, handle) = OutputStream::try_default().map_err(|err| err.to_string())?;
+
+      Ok(RodioBackend {
+         _stream: stream,
+         handle,
+         samples: Sound::ALL.iter().map(|sound| (*sound, sound.samples())).collect(),
+         loops: HashMap::new(),
+         volumes: [1.0; 2],
+         paused: false
+      })
+   }
+
+   fn buffer(&self, sound: Sound) -> SamplesBuffer<f32> {
+      SamplesBuffer::new(1, synth::SAMPLE_RATE, self.samples[&sound].clone())
+   }
+
+   fn sink(&self, sound: Sound) -> Option<Sink> {
+      match Sink::try_new(&self.handle) {
+         Err(err) => {
+            eprintln!("Failed to play sound: {}", err);
+            None
+         },
+         Ok(sink) => {
+            sink.set_volume(self.volumes[sound.channel().index()]);
+            Some(sink)
+         }
+      }
+   }
+}
+
+impl AudioBackend for RodioBackend {
+   fn play(&mut self, sound: Sound) {
+      if self.paused || self.volumes[sound.channel().index()] <= 0.0 {
+         return;
+      }
+
+      if let Some(sink) = self.sink(sound) {
+         sink.append(self.buffer(sound));
+         // Keeps playing on its own until the sound ends
+         sink.detach();
+      }
+   }
+
+   fn start_loop(&mut self, sound: Sound) {
+      if self.loops.contains_key(&sound) {
+         return;
+      }
+
+      if let Some(sink) = self.sink(sound) {
+         sink.append(self.buffer(sound).repeat_infinite());
+
+         if self.paused {
+            sink.pause();
+         }
+
+         self.loops.insert(sound, sink);
+      }
+   }
+
+   fn stop_loop(&mut self, sound: Sound) {
+      if let Some(sink) = self.loops.remove(&sound) {
+         sink.stop();
+      }
+   }
+
+   fn set_paused(&mut self, paused: bool) {
+      if self.paused == paused {
+         return;
+      }
+
+      self.paused = paused;
+
+      for sink in self.loops.values() {
+         if paused { sink.pause() } else { sink.play() }
+      }
+   }
+
+   fn set_volume(&mut self, channel: Channel, volume: f32) {
+      self.volumes[channel.index()] = volume.clamp(0.0, 1.0);
+
+      for (sound, sink) in self.loops.iter() {
+         if sound.channel() == channel {
+            sink.set_volume(self.volumes[channel.index()]);
+         }
+      }
+   }
+}
+
+/*
+ * The four note heartbeat of the formation. Its tempo is given from outside
+ * every tick, so it speeds up as soon as the formation does.
+ */
+#[derive(Default)]
+struct March {
+   note: usize,
+   // Seconds since the last note
+   elapsed: f32
+}
+
+/*
+ * Turns game events into sound.
+ */
+pub struct Audio {
+   backend: Box<dyn AudioBackend>,
+   march: March
+}
+
+impl Audio {
+   /*
+    * Uses the default audio device, or stays silent if there isn't one.
+    */
+   pub fn new() -> Self {
+      match RodioBackend::new() {
+         Err(err) => {
+            eprintln!("No audio device, sound is disabled: {}", err);
+            Audio::silent()
+         },
+         Ok(backend) => Audio { backend: Box::new(backend), march: March::default() }
+      }
+   }
+
+   pub fn silent() -> Self {
+      Audio { backend: Box::new(NullBackend), march: March::default() }
+   }
+
+   /*
+    * Takes effect right away, also for sounds that are already playing.
+    */
+   pub fn apply_settings(&mut self, settings: &AudioSettings) {
+      self.backend.set_volume(Channel::Sfx, settings.volume(Channel::Sfx));
+      self.backend.set_volume(Channel::Music, settings.volume(Channel::Music));
+   }
+
+   /*
+    * Pauses loops and drops new sounds, e.g. while the pause menu is open.
+    */
+   pub fn set_paused(&mut self, paused: bool) {
+      self.backend.set_paused(paused);
+   }
+
+   /*
+    * Advances the march by dt seconds, playing the next note once interval
+    * seconds have passed since the last one.
+    */
+   pub fn update_march(&mut self, dt: f32, interval: f32) {
+      self.march.elapsed += dt;
+
+      if self.march.elapsed >= interval {
+         self.march.elapsed = 0.0;
+         self.backend.play(Sound::MARCH[self.march.note]);
+         self.march.note = (self.march.note + 1) % Sound::MARCH.len();
+      }
+   }
+
+   /*
+    * Starts the march over from its first note, e.g. for a new wave.
+    */
+   pub fn reset_march(&mut self) {
+      self.march = March::default();
+   }
+
+   pub fn stop_loops(&mut self) {
+      self.backend.stop_loop(Sound::Ufo);
+   }
+}
+
+impl Subscriber for Audio {
+   fn notify(&mut self, _tick: u64, event: &GameEvent) {
+      match event {
+         GameEvent::ShotFired => self.backend.play(Sound::PlayerShot),
+         GameEvent::InvaderKilled { .. } => self.backend.play(Sound::InvaderKilled),
+         GameEvent::PlayerHit => self.backend.play(Sound::PlayerExplosion),
+         GameEvent::GameOver { .. } => self.stop_loops(),
+         GameEvent::UfoSpawned => self.backend.start_loop(Sound::Ufo),
+         GameEvent::UfoLeft => self.backend.stop_loop(Sound::Ufo),
+         GameEvent::UfoKilled { .. } => {
+            self.backend.stop_loop(Sound::Ufo);
+            self.backend.play(Sound::InvaderKilled);
+         },
+         GameEvent::ExtraLife => self.backend.play(Sound::ExtraLife),
+         GameEvent::GameStarted { .. } | GameEvent::LifeLost { .. } | GameEvent::WaveCleared { .. } => {}
+      }
+   }
+}
@@ -0,0 +1,127 @@
+use std::f32::consts::TAU;
+
+pub const SAMPLE_RATE: u32 = 44100;
+
+/*
+ * Tiny xorshift generator for noise. Sounds are generated the same way on
+ * every run, so they don't need the game's random numbers.
+ */
+struct Noise(u32);
+
+impl Noise {
+   fn next(&mut self) -> f32 {
+      self.0 ^= self.0 << 13;
+      self.0 ^= self.0 >> 17;
+      self.0 ^= self.0 << 5;
+
+      (self.0 as f32 / u32::MAX as f32) * 2.0 - 1.0
+   }
+}
+
+fn sample_count(seconds: f32) -> usize {
+   (seconds * SAMPLE_RATE as f32) as usize
+}
+
+fn square(phase: f32) -> f32 {
+   if phase.fract() < 0.5 { 1.0 } else { -1.0 }
+}
+
+/*
+ * Square wave whose frequency goes from start to end (in Hz) over the
+ * length of the sound, fading out linearly.
+ */
+fn sweep(seconds: f32, start: f32, end: f32, volume: f32) -> Vec<f32> {
+   let count = sample_count(seconds);
+   let mut phase = 0.0;
+
+   (0..count).map(|index| {
+      let progress = index as f32 / count as f32;
+      let frequency = start + (end - start) * progress;
+      phase += frequency / SAMPLE_RATE as f32;
+
+      square(phase) * volume * (1.0 - progress)
+   }).collect()
+}
+
+/*
+ * Short falling "pew".
+ */
+pub fn player_shot() -> Vec<f32> {
+   sweep(0.18, 1400.0, 200.0, 0.25)
+}
+
+/*
+ * Crunchy noise over a falling tone.
+ */
+pub fn invader_killed() -> Vec<f32> {
+   let mut noise = Noise(0x1234_5678);
+   let tone = sweep(0.3, 700.0, 80.0, 0.2);
+
+   tone.iter().enumerate().map(|(index, sample)| {
+      let fade = 1.0 - index as f32 / tone.len() as f32;
+      sample + noise.next() * 0.15 * fade * fade
+   }).collect()
+}
+
+/*
+ * Low rumble: noise, smoothed so it loses its hiss, with a long tail.
+ */
+pub fn player_explosion() -> Vec<f32> {
+   let mut noise = Noise(0x9e37_79b9);
+   let count = sample_count(1.2);
+   let mut smoothed = 0.0;
+
+   (0..count).map(|index| {
+      let fade = 1.0 - index as f32 / count as f32;
+      smoothed += (noise.next() - smoothed) * 0.08;
+
+      smoothed * 1.6 * fade * fade
+   }).collect()
+}
+
+/*
+ * The warbling siren of the flying saucer, meant to be looped. The pitch
+ * wobbles around 600 Hz a whole number of times, so the end lines up with
+ * the start and the loop doesn't click.
+ */
+pub fn ufo_loop() -> Vec<f32> {
+   let count = sample_count(0.5);
+   let mut phase = 0.0;
+
+   (0..count).map(|index| {
+      let time = index as f32 / SAMPLE_RATE as f32;
+      let frequency = 600.0 + 200.0 * (TAU * 8.0 * time).sin();
+      phase += frequency / SAMPLE_RATE as f32;
+
+      (TAU * phase).sin() * 0.2
+   }).collect()
+}
+
+/*
+ * Rising four note jingle.
+ */
+pub fn extra_life() -> Vec<f32> {
+   [523.25, 659.25, 783.99, 1046.5].iter()
+                                   .flat_map(|frequency| sweep(0.09, *frequency, *frequency, 0.2))
+                                   .collect()
+}
+
+/*
+ * One of the four bass notes of the invaders' march, walking down from F.
+ * Quick decay, so it thumps rather than hums.
+ */
+pub fn march_note(index: usize) -> Vec<f32> {
+   const NOTES: [f32; 4] = [87.31, 77.78, 69.30, 65.41];
+
+   let count = sample_count(0.12);
+   let frequency = NOTES[index % NOTES.len()];
+   let mut phase = 0.0;
+
+   (0..count).map(|sample| {
+      let progress = sample as f32 / count as f32;
+      phase += frequency / SAMPLE_RATE as f32;
+
+      // A clipped sine is square-ish without the buzz of hard corners
+      (TAU * phase).sin().clamp(-0.6, 0.6) * 0.5 * (1.0 - progress).powi(2)
+   }).collect()
+}
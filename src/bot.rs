@@ -0,0 +1,166 @@
+use ggez::graphics::Rect;
+
+use crate::input_source::InputFrame;
+use crate::simulation::Simulation;
+use crate::viewport::PLAYFIELD_WIDTH;
+
+// How far ahead, in seconds, the bot looks out for enemy bullets
+const DODGE_HORIZON: f32 = 1.2;
+// Room left between the player and a bullet it's dodging, in player widths
+const DODGE_MARGIN: f32 = 0.3;
+
+/*
+ * A built-in player. Looks at the simulation the way a person looks at the
+ * screen: gets out from under enemy bullets first, then lines up with the
+ * nearest invader (or the UFO) and fires.
+ */
+#[derive(Default)]
+pub struct Autopilot {
+   // Leftmost invader and how many were alive on the last tick, to guess
+   // where the formation is going
+   formation: Option<(f32, usize)>,
+   formation_velocity: f32,
+   ufo_x: Option<f32>,
+   // The player's bullet as last seen in the air. Speeds on the playfield
+   // depend on the sprite sizes, so it's measured instead of taken from the
+   // config
+   bullet: Option<Rect>,
+   bullet_speed: Option<f32>
+}
+
+impl Autopilot {
+   pub fn new() -> Self {
+      Self::default()
+   }
+
+   /*
+    * The input for the next tick of sim, dt seconds long.
+    */
+   pub fn frame(&mut self, sim: &Simulation, dt: f32) -> InputFrame {
+      let image_dimensions = sim.image_dimensions();
+      let config = sim.state.config();
+
+      let player = sim.state.player_bounds(image_dimensions);
+      let enemies = sim.state.enemy_bounds(image_dimensions);
+      let ufo = sim.state.ufo_bounds(image_dimensions);
+      let ufo_velocity = self.track(&enemies, ufo, dt);
+      let player_bullet = sim.state.player_bullet_bounds(image_dimensions);
+      self.track_bullet(player_bullet, dt);
+
+      let step = config.player.speed * dt;
+      let right_wall = PLAYFIELD_WIDTH - player.w - step;
+
+      // Player positions that get hit soon if the bullet keeps falling. A
+      // bullet that got past the top of the player still hits it until it's
+      // off the playfield
+      let danger: Vec<(f32, f32)> = sim.state.enemy_bullet_bounds(image_dimensions)
+                                             .iter()
+                                             .filter(|bullet| player.y - (bullet.y + bullet.h) < config.enemies.bullet_speed * DODGE_HORIZON)
+                                             .map(|bullet| {
+                                                let margin = player.w * DODGE_MARGIN;
+                                                (bullet.x - player.w - margin, bullet.x + bullet.w + margin)
+                                             })
+                                             .collect();
+
+      let is_safe = |x: f32| danger.iter().all(|(left, right)| x < *left || x > *right);
+
+      // Where the bullet would have to leave from to hit a target, given
+      // how long it takes to get there
+      let bullet_speed = self.bullet_speed.unwrap_or(config.player.bullet_speed);
+      let bullet_width = self.bullet.map_or(0.0, |bullet| bullet.w);
+      let aim = |target: &Rect, velocity: f32| {
+         let flight = (player.y - target.y).max(0.0) / bullet_speed;
+         target.x + (target.w - bullet_width) / 2.0 + velocity * flight
+      };
+
+      let target = ufo.map(|ufo| (ufo, aim(&ufo, ufo_velocity)))
+                      .filter(|(_, x)| (0.0..=right_wall).contains(x))
+                      .or_else(|| {
+                         enemies.iter()
+                                .map(|enemy| (*enemy, aim(enemy, self.formation_velocity)))
+                                .min_by(|(a, a_x), (b, b_x)| {
+                                   // The nearest invader, the lower one if they're in a column
+                                   let a_key = (a_x - player.x).abs() - a.y;
+                                   let b_key = (b_x - player.x).abs() - b.y;
+                                   a_key.total_cmp(&b_key)
+                                })
+                      });
+
+      let destination = if !is_safe(player.x) {
+         nearest_safe(player.x, &danger, right_wall, &is_safe)
+      } else {
+         match target {
+            Some((_, x)) => {
+               let next = player.x + (x - player.x).clamp(-step, step);
+
+               // Wait where it's safe rather than walk into a bullet
+               if is_safe(next) { x.clamp(0.0, right_wall) } else { player.x }
+            },
+            None => player.x
+         }
+      };
+
+      let movement = if step > 0.0 { (destination - player.x) / step } else { 0.0 };
+
+      let fire = match target {
+         Some((target, x)) => player_bullet.is_none() && (x - player.x).abs() < target.w / 4.0,
+         None => false
+      };
+
+      InputFrame::new(movement, fire)
+   }
+
+   /*
+    * Updates the guess of how fast the formation moves sideways, and returns
+    * the UFO's speed.
+    */
+   fn track(&mut self, enemies: &[Rect], ufo: Option<Rect>, dt: f32) -> f32 {
+      let leftmost = enemies.iter().map(|enemy| enemy.x).reduce(f32::min);
+
+      match (leftmost, self.formation) {
+         // When an invader on the left edge dies the leftmost one jumps,
+         // which isn't the formation moving
+         (Some(x), Some((last_x, last_alive))) if last_alive == enemies.len() && dt > 0.0 => {
+            self.formation_velocity = (x - last_x) / dt;
+         },
+         (None, _) => self.formation_velocity = 0.0,
+         _ => {}
+      }
+
+      self.formation = leftmost.map(|x| (x, enemies.len()));
+
+      let ufo_x = ufo.map(|ufo| ufo.x);
+      let ufo_velocity = match (ufo_x, self.ufo_x) {
+         (Some(x), Some(last_x)) if dt > 0.0 => (x - last_x) / dt,
+         _ => 0.0
+      };
+
+      self.ufo_x = ufo_x;
+      ufo_velocity
+   }
+
+   fn track_bullet(&mut self, bullet: Option<Rect>, dt: f32) {
+      if let (Some(bullet), Some(last)) = (bullet, self.bullet) {
+         // A new bullet starts lower than where the last one was
+         if bullet.y < last.y && dt > 0.0 {
+            self.bullet_speed = Some((last.y - bullet.y) / dt);
+         }
+      }
+
+      if bullet.is_some() {
+         self.bullet = bullet;
+      }
+   }
+}
+
+/*
+ * The closest position next to the dangerous ones that is safe and on the
+ * playfield, or x itself if there's nowhere to go.
+ */
+fn nearest_safe(x: f32, danger: &[(f32, f32)], right_wall: f32, is_safe: &impl Fn(f32) -> bool) -> f32 {
+   danger.iter()
+         .flat_map(|(left, right)| [left - 1.0, right + 1.0])
+         .filter(|candidate| (0.0..=right_wall).contains(candidate) && is_safe(*candidate))
+         .min_by(|a, b| (a - x).abs().total_cmp(&(b - x).abs()))
+         .unwrap_or(x)
+}
@@ -0,0 +1,295 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ggez::Context;
+use ggez::graphics::ImageFormat;
+use image::RgbaImage;
+use image::imageops::{self, FilterType};
+
+use crate::toast::Toasts;
+use crate::viewport::Viewport;
+
+// Clips are recorded smaller and slower than the game runs to keep the
+// frames of the last few seconds in memory and the files shareable
+const CLIP_FPS: u32 = 15;
+const CLIP_WIDTH: u32 = 400;
+const CLIP_HEIGHT: u32 = 225;
+// How hard the GIF encoder works on each frame's palette, 1 is best and
+// slowest, 30 is worst and fastest
+const CLIP_QUANTIZE_SPEED: i32 = 10;
+
+pub const DEFAULT_CLIP_SECONDS: u32 = 10;
+
+struct Clip {
+   frames: VecDeque<RgbaImage>,
+   // Time since the last frame was kept, in seconds
+   since_frame: f32
+}
+
+/*
+ * Screenshots and GIF clips of the window, saved to the data directory.
+ * Frames are read back from the window right before the next one is drawn,
+ * so what's saved is exactly what was on screen, without the black bars.
+ * Files are written on another thread, the result shows up as a toast.
+ */
+pub struct Capture {
+   dir: PathBuf,
+   clip_seconds: u32,
+   screenshot_requested: bool,
+   clip: Option<Clip>,
+   saved_sender: Sender<Result<PathBuf, String>>,
+   saved: Receiver<Result<PathBuf, String>>
+}
+
+impl Capture {
+   pub fn new(dir: &Path, clip_seconds: u32) -> Self {
+      let (saved_sender, saved) = mpsc::channel();
+
+      Capture {
+         dir: PathBuf::from(dir),
+         clip_seconds,
+         screenshot_requested: false,
+         clip: None,
+         saved_sender,
+         saved
+      }
+   }
+
+   /*
+    * Saves the frame on screen the next time update is called.
+    */
+   pub fn request_screenshot(&mut self) {
+      self.screenshot_requested = true;
+   }
+
+   /*
+    * Starts keeping the last clip_seconds of frames, or stops and saves
+    * them as a GIF.
+    */
+   pub fn toggle_recording(&mut self, toasts: &mut Toasts) {
+      match self.clip.take() {
+         None => {
+            self.clip = Some(Clip { frames: VecDeque::new(), since_frame: 0.0 });
+            toasts.push(&format!("RECORDING  THE  LAST  {}  SECONDS", self.clip_seconds));
+         },
+         Some(clip) if clip.frames.is_empty() => toasts.push("RECORDING  STOPPED"),
+         Some(clip) => {
+            let (path, file) = match create_unique_file(&self.dir, "clip", "gif") {
+               Ok(created) => created,
+               Err(err) => return report_failure(toasts, &err)
+            };
+            let sender = self.saved_sender.clone();
+            toasts.push("SAVING  CLIP");
+
+            thread::spawn(move || {
+               let _ = sender.send(finish_saving(path, save_gif(file, clip.frames)));
+            });
+         }
+      }
+   }
+
+   /*
+    * Reads back the frame that is on screen if a screenshot or the clip
+    * needs it. Call before drawing the next frame, dt seconds after the
+    * last one.
+    */
+   pub fn update(&mut self, ctx: &Context, viewport: &Viewport, dt: f32, toasts: &mut Toasts) {
+      for saved in self.saved.try_iter() {
+         match saved {
+            Ok(path) => {
+               println!("Saved {}", path.display());
+               toasts.push(if path.extension().is_some_and(|extension| extension == "gif") { "CLIP  SAVED" } else { "SCREENSHOT  SAVED" });
+            },
+            Err(err) => report_failure(toasts, &err)
+         }
+      }
+
+      let clip_frame_due = match self.clip.as_mut() {
+         Some(clip) => {
+            clip.since_frame += dt;
+            clip.since_frame >= 1.0 / CLIP_FPS as f32
+         },
+         None => false
+      };
+
+      if !self.screenshot_requested && !clip_frame_due {
+         return;
+      }
+
+      let frame = match read_playfield(ctx, viewport) {
+         Ok(frame) => frame,
+         Err(err) => {
+            eprintln!("Failed to read the frame: {}", err);
+            self.screenshot_requested = false;
+            return;
+         }
+      };
+
+      if clip_frame_due {
+         if let Some(clip) = self.clip.as_mut() {
+            clip.since_frame %= 1.0 / CLIP_FPS as f32;
+            clip.frames.push_back(imageops::resize(&frame, CLIP_WIDTH, CLIP_HEIGHT, FilterType::Triangle));
+
+            while clip.frames.len() > (self.clip_seconds * CLIP_FPS) as usize {
+               clip.frames.pop_front();
+            }
+         }
+      }
+
+      if self.screenshot_requested {
+         self.screenshot_requested = false;
+
+         let (path, file) = match create_unique_file(&self.dir, "screenshot", "png") {
+            Ok(created) => created,
+            Err(err) => return report_failure(toasts, &err)
+         };
+         let sender = self.saved_sender.clone();
+
+         thread::spawn(move || {
+            let saved = frame.write_to(&mut BufWriter::new(file), image::ImageFormat::Png).map_err(|err| err.to_string());
+            let _ = sender.send(finish_saving(path, saved));
+         });
+      }
+   }
+}
+
+/*
+ * The playfield part of the frame last drawn to the window, as RGBA.
+ */
+fn read_playfield(ctx: &Context, viewport: &Viewport) -> Result<RgbaImage, String> {
+   let frame = ctx.gfx.frame();
+   let mut pixels = frame.to_pixels(ctx).map_err(|err| err.to_string())?;
+
+   match frame.format() {
+      ImageFormat::Rgba8Unorm | ImageFormat::Rgba8UnormSrgb => {},
+      ImageFormat::Bgra8Unorm | ImageFormat::Bgra8UnormSrgb => {
+         for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+         }
+      },
+      format => return Err(format!("can't read frames in the {:?} format", format))
+   }
+
+   let mut image = RgbaImage::from_raw(frame.width(), frame.height(), pixels)
+                             .ok_or_else(|| String::from("the frame has the wrong size"))?;
+
+   // Nothing that was drawn is transparent, the window just doesn't care
+   for pixel in image.pixels_mut() {
+      pixel.0[3] = 255;
+   }
+
+   let playfield = viewport.playfield_pixels();
+   let x = (playfield.x.max(0.0) as u32).min(image.width());
+   let y = (playfield.y.max(0.0) as u32).min(image.height());
+   let width = (playfield.w as u32).min(image.width() - x);
+   let height = (playfield.h as u32).min(image.height() - y);
+
+   Ok(imageops::crop_imm(&image, x, y, width, height).to_image())
+}
+
+fn report_failure(toasts: &mut Toasts, err: &str) {
+   eprintln!("{}", err);
+   toasts.push("CAPTURE  FAILED");
+}
+
+/*
+ * What the save thread sends back. A file that couldn't be written is
+ * removed, the name is free again for the next try.
+ */
+fn finish_saving(path: PathBuf, saved: Result<(), String>) -> Result<PathBuf, String> {
+   saved.map(|_| path.clone()).map_err(|err| {
+      let _ = fs::remove_file(&path);
+      format!("Failed to save {}: {}", path.display(), err)
+   })
+}
+
+fn save_gif(file: File, frames: VecDeque<RgbaImage>) -> Result<(), String> {
+   let mut encoder = gif::Encoder::new(BufWriter::new(file), CLIP_WIDTH as u16, CLIP_HEIGHT as u16, &[])
+                                  .map_err(|err| err.to_string())?;
+   encoder.set_repeat(gif::Repeat::Infinite).map_err(|err| err.to_string())?;
+
+   for frame in frames {
+      let mut pixels = frame.into_raw();
+      let mut frame = gif::Frame::from_rgba_speed(CLIP_WIDTH as u16, CLIP_HEIGHT as u16, &mut pixels, CLIP_QUANTIZE_SPEED);
+      // In hundredths of a second
+      frame.delay = (100 / CLIP_FPS) as u16;
+      encoder.write_frame(&frame).map_err(|err| err.to_string())?;
+   }
+
+   Ok(())
+}
+
+/*
+ * Creates dir/<prefix>-<date>_<time>.<extension>, with a number added if a
+ * file with that name was already saved in the same second. The file is
+ * created right away, so two captures in the same second can't pick the
+ * same name while the first is still being written on its thread.
+ */
+fn create_unique_file(dir: &Path, prefix: &str, extension: &str) -> Result<(PathBuf, File), String> {
+   let stamp = timestamp(SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or(0));
+   let mut path = dir.join(format!("{}-{}.{}", prefix, stamp, extension));
+   let mut count = 2;
+
+   loop {
+      match OpenOptions::new().write(true).create_new(true).open(&path) {
+         Ok(file) => return Ok((path, file)),
+         Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+            path = dir.join(format!("{}-{}-{}.{}", prefix, stamp, count, extension));
+            count += 1;
+         },
+         Err(err) => return Err(format!("Failed to create {}: {}", path.display(), err))
+      }
+   }
+}
+
+/*
+ * Seconds since the epoch as a UTC date and time, YYYY-MM-DD_HH-MM-SS.
+ */
+fn timestamp(seconds: u64) -> String {
+   let (days, time) = (seconds / 86400, seconds % 86400);
+
+   // Days since 1970-01-01 to a date, see
+   // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+   let z = days as i64 + 719468;
+   let era = z.div_euclid(146097);
+   let day_of_era = z.rem_euclid(146097);
+   let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+   let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+   let month_index = (5 * day_of_year + 2) / 153;
+   let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+   let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+   let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+   format!("{:04}-{:02}-{:02}_{:02}-{:02}-{:02}", year, month, day, time / 3600, time / 60 % 60, time % 60)
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn timestamps_are_utc_dates() {
+      assert_eq!(timestamp(0), "1970-01-01_00-00-00");
+      assert_eq!(timestamp(951_868_799), "2000-02-29_23-59-59");
+      assert_eq!(timestamp(1_700_000_000), "2023-11-14_22-13-20");
+   }
+
+   #[test]
+   fn captures_in_the_same_second_get_their_own_file() {
+      let dir = std::env::temp_dir().join(format!("space_invaders_capture_{}", std::process::id()));
+      fs::create_dir_all(&dir).unwrap();
+
+      // Neither file is written to yet, the names are taken all the same
+      let (first, _) = create_unique_file(&dir, "clip", "gif").unwrap();
+      let (second, _) = create_unique_file(&dir, "clip", "gif").unwrap();
+      fs::remove_dir_all(&dir).unwrap();
+
+      assert_ne!(first, second);
+      assert_eq!(first.parent(), second.parent());
+   }
+}
@@ -0,0 +1,132 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::capture::DEFAULT_CLIP_SECONDS;
+use crate::difficulty::Difficulty;
+
+/*
+ * Command line options. Everything is optional, without any the game starts
+ * the way it always did.
+ */
+#[derive(Debug, Parser)]
+#[command(name = "space_invaders", version, about = "(Almost) Space Invaders!")]
+pub struct Cli {
+   /// Start in a window (the default)
+   #[arg(long, conflicts_with = "fullscreen")]
+   pub windowed: bool,
+
+   /// Start in fullscreen, F11 or Alt+Enter switches back
+   #[arg(long)]
+   pub fullscreen: bool,
+
+   /// Window size in pixels, e.g. 1920x1080
+   #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = parse_resolution, default_value = "1280x720")]
+   pub resolution: (f32, f32),
+
+   /// Seed for everything random in the game, the same seed and the same
+   /// input always play out the same way. Random if not given
+   #[arg(long)]
+   pub seed: Option<u64>,
+
+   /// How hard the game is
+   #[arg(long, value_enum, default_value_t = Difficulty::Normal)]
+   pub difficulty: Difficulty,
+
+   /// The wave to start at, later waves start with faster enemies
+   #[arg(long, value_name = "WAVE", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+   pub start_wave: u32,
+
+   /// Directory with the sprite sheet and the font [default: src/resources]
+   #[arg(long, value_name = "DIR")]
+   pub resources: Option<PathBuf>,
+
+   /// Gameplay config file [default: config.toml in the resources directory]
+   #[arg(long, value_name = "FILE")]
+   pub config: Option<PathBuf>,
+
+   /// Directory for the high scores and the settings file [default: the
+   /// resources directory]
+   #[arg(long, value_name = "DIR")]
+   pub data_dir: Option<PathBuf>,
+
+   /// Play back a replay file. Its seed, difficulty and starting wave are
+   /// used instead of the ones given here
+   #[arg(long, value_name = "FILE")]
+   pub replay: Option<PathBuf>,
+
+   /// Record every game to a replay file, overwriting it each game
+   #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+   pub record: Option<PathBuf>,
+
+   /// Let a player on another machine play: connect to ADDRESS (host:port)
+   /// and take the controls from the frames it sends, one "<movement>
+   /// <fire>" line per tick like in a replay file
+   #[arg(long, value_name = "ADDRESS", conflicts_with_all = ["replay", "record", "headless", "tui", "env", "snapshot", "verify_snapshots"])]
+   pub remote: Option<String>,
+
+   /// Run the game without a window or sound and print the result. Plays the
+   /// --replay file if given, the bot with --bot, otherwise the player stands
+   /// still
+   #[arg(long)]
+   pub headless: bool,
+
+   /// Play in the terminal instead of a window
+   #[arg(long, conflicts_with_all = ["headless", "env", "replay", "record"])]
+   pub tui: bool,
+
+   /// Let the built-in bot play the headless game
+   #[arg(long, requires = "headless", conflicts_with = "replay")]
+   pub bot: bool,
+
+   /// Stop a headless game after this many ticks (60 per second) [default:
+   /// one hour]
+   #[arg(long, value_name = "TICKS", requires = "headless")]
+   pub max_ticks: Option<u64>,
+
+   /// Serve the reinforcement learning environment over stdin and stdout
+   /// instead of playing, see gym.rs for the protocol
+   #[arg(long, conflicts_with_all = ["headless", "replay", "record"])]
+   pub env: bool,
+
+   /// Include grayscale frames in the environment's observations
+   #[arg(long, requires = "env")]
+   pub env_frames: bool,
+
+   /// Ticks each environment step lasts
+   #[arg(long, value_name = "TICKS", default_value_t = 1, requires = "env", value_parser = clap::value_parser!(u32).range(1..))]
+   pub frame_skip: u32,
+
+   /// Write every game event (shots, kills, hits, waves, game overs) to
+   /// FILE as JSON Lines, with the tick it happened on
+   #[arg(long, value_name = "FILE", conflicts_with_all = ["tui", "env", "snapshot", "verify_snapshots"])]
+   pub event_log: Option<PathBuf>,
+
+   /// How many seconds a clip keeps, F9 starts and saves one
+   #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_CLIP_SECONDS, value_parser = clap::value_parser!(u32).range(1..))]
+   pub clip_seconds: u32,
+
+   /// Draw the title screen, the HUD and a wave in progress without a
+   /// window and save them as PNGs in DIR
+   #[arg(long, value_name = "DIR", conflicts_with_all = ["headless", "tui", "env", "replay", "record"])]
+   pub snapshot: Option<PathBuf>,
+
+   /// Compare those snapshots against the golden images in DIR and exit
+   /// with an error if any of them changed
+   #[arg(long, value_name = "DIR", conflicts_with_all = ["snapshot", "headless", "tui", "env", "replay", "record"])]
+   pub verify_snapshots: Option<PathBuf>
+}
+
+fn parse_resolution(value: &str) -> Result<(f32, f32), String> {
+   let (width, height) = value.split_once(['x', 'X'])
+                              .ok_or_else(|| format!("expected WIDTHxHEIGHT, got {}", value))?;
+
+   let width = width.trim().parse::<u32>().map_err(|err| format!("invalid width {}: {}", width, err))?;
+   let height = height.trim().parse::<u32>().map_err(|err| format!("invalid height {}: {}", height, err))?;
+
+   if width == 0 || height == 0 {
+      return Err(String::from("the resolution can't be zero"));
+   }
+
+   Ok((width as f32, height as f32))
+}
@@ -0,0 +1,309 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{PLAYER_MAX_LIVES, PLAYER_NUM_LIVES, UFO_POINTS};
+use crate::lives::ExtraLifeConfig;
+
+/*
+ * Gameplay tuning, read from a TOML file so it can be changed without
+ * recompiling. Every value has a default, a file only needs the ones it
+ * changes:
+ *
+ *    [enemies]
+ *    per_row = 9
+ *    bullet_speed = 420.0
+ *
+ * Speeds are in playfield units per second, times in seconds.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+   pub player: PlayerConfig,
+   pub enemies: EnemyConfig,
+   pub ufo: UfoConfig,
+   pub scoring: ScoringConfig
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlayerConfig {
+   pub lives: u8,
+   pub max_lives: u8,
+   pub speed: f32,
+   pub bullet_speed: f32,
+   pub scale: f32
+}
+
+impl Default for PlayerConfig {
+   fn default() -> Self {
+      PlayerConfig {
+         lives: PLAYER_NUM_LIVES,
+         max_lives: PLAYER_MAX_LIVES,
+         speed: 360.0,
+         bullet_speed: 540.0,
+         scale: 1.0
+      }
+   }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnemyConfig {
+   pub rows: u8,
+   pub per_row: u8,
+   pub scale: f32,
+   // The formation moves speed_unit units per second for every step of speed
+   pub starting_speed: u8,
+   pub speed_per_wave: u8,
+   pub speed_unit: f32,
+   pub bullet_speed: f32,
+   // With at most this many invaders left, all of them fire at once
+   pub all_fire_at: usize,
+   // Up to large_volley invaders fire at once while more than
+   // volley_threshold are alive, up to small_volley after that
+   pub volley_threshold: usize,
+   pub large_volley: u16,
+   pub small_volley: u16
+}
+
+impl Default for EnemyConfig {
+   fn default() -> Self {
+      EnemyConfig {
+         rows: 3,
+         per_row: 7,
+         scale: 0.55,
+         starting_speed: 2,
+         speed_per_wave: 1,
+         speed_unit: 60.0,
+         bullet_speed: 360.0,
+         all_fire_at: 5,
+         volley_threshold: 20,
+         large_volley: 5,
+         small_volley: 3
+      }
+   }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UfoConfig {
+   pub speed: f32,
+   pub scale: f32,
+   // Seconds between two flights, plus up to interval_spread at random
+   pub interval: f32,
+   pub interval_spread: f32
+}
+
+impl Default for UfoConfig {
+   fn default() -> Self {
+      UfoConfig {
+         speed: 240.0,
+         scale: 0.45,
+         interval: 20.0,
+         interval_spread: 10.0
+      }
+   }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoringConfig {
+   pub enemy_points: u64,
+   pub ufo_points: u64,
+   // See ExtraLifeConfig
+   pub extra_life_at: Vec<u64>,
+   pub extra_life_every: Option<u64>
+}
+
+impl Default for ScoringConfig {
+   fn default() -> Self {
+      ScoringConfig {
+         enemy_points: 10,
+         ufo_points: UFO_POINTS,
+         extra_life_at: vec![1500],
+         extra_life_every: None
+      }
+   }
+}
+
+impl GameConfig {
+   /*
+    * Reads and validates a configuration file.
+    */
+   pub fn load(path: &Path) -> Result<GameConfig, String> {
+      let contents = fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+      let config = toml::from_str::<GameConfig>(&contents).map_err(|err| format!("Invalid config {}: {}", path.display(), err))?;
+
+      config.validate().map_err(|err| format!("Invalid config {}: {}", path.display(), err))?;
+      Ok(config)
+   }
+
+   /*
+    * Checks for values the game can't run with. Returns every problem found,
+    * separated by semicolons.
+    */
+   pub fn validate(&self) -> Result<(), String> {
+      let mut problems = Vec::new();
+
+      let speeds = [
+         ("player.speed", self.player.speed),
+         ("player.bullet_speed", self.player.bullet_speed),
+         ("player.scale", self.player.scale),
+         ("enemies.scale", self.enemies.scale),
+         ("enemies.speed_unit", self.enemies.speed_unit),
+         ("enemies.bullet_speed", self.enemies.bullet_speed),
+         ("ufo.speed", self.ufo.speed),
+         ("ufo.scale", self.ufo.scale),
+         ("ufo.interval", self.ufo.interval)
+      ];
+
+      for (name, value) in speeds {
+         if !value.is_finite() || value <= 0.0 {
+            problems.push(format!("{} must be greater than 0", name));
+         }
+      }
+
+      if !self.ufo.interval_spread.is_finite() || self.ufo.interval_spread < 0.0 {
+         problems.push(String::from("ufo.interval_spread can't be negative"));
+      }
+
+      if self.player.lives == 0 || self.player.lives > self.player.max_lives {
+         problems.push(String::from("player.lives must be between 1 and player.max_lives"));
+      }
+
+      if self.enemies.rows == 0 || self.enemies.per_row == 0 {
+         problems.push(String::from("enemies.rows and enemies.per_row must be at least 1"));
+      } else if self.enemies.rows as u32 * self.enemies.per_row as u32 > u8::MAX as u32 {
+         problems.push(format!("there can't be more than {} enemies", u8::MAX));
+      }
+
+      if self.enemies.starting_speed == 0 {
+         problems.push(String::from("enemies.starting_speed must be at least 1"));
+      }
+
+      if self.enemies.large_volley == 0 || self.enemies.small_volley == 0 {
+         problems.push(String::from("enemies.large_volley and enemies.small_volley must be at least 1"));
+      } else if self.enemies.rows > 0 && self.enemies.per_row > 0 &&
+                self.enemies.large_volley.max(self.enemies.small_volley) as u32 > self.enemies.rows as u32 * self.enemies.per_row as u32 {
+         problems.push(String::from("enemies.large_volley and enemies.small_volley can't be more than the number of enemies"));
+      }
+
+      if problems.is_empty() {
+         Ok(())
+      } else {
+         Err(problems.join("; "))
+      }
+   }
+
+   pub fn num_enemies(&self) -> u8 {
+      self.enemies.rows * self.enemies.per_row
+   }
+
+   pub fn extra_life_config(&self) -> ExtraLifeConfig {
+      ExtraLifeConfig {
+         thresholds: self.scoring.extra_life_at.clone(),
+         every: self.scoring.extra_life_every
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use std::path::PathBuf;
+
+   use super::*;
+
+   fn problems(config: &GameConfig) -> Vec<String> {
+      match config.validate() {
+         Ok(()) => Vec::new(),
+         Err(err) => err.split("; ").map(String::from).collect()
+      }
+   }
+
+   #[test]
+   fn defaults_and_the_shipped_config_are_valid() {
+      assert_eq!(GameConfig::default().validate(), Ok(()));
+
+      let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src").join("resources").join("config.toml");
+      assert!(GameConfig::load(&path).is_ok());
+   }
+
+   #[test]
+   fn speeds_must_be_positive() {
+      let mut config = GameConfig::default();
+      config.player.speed = 0.0;
+      config.enemies.bullet_speed = -360.0;
+      config.ufo.interval = f32::NAN;
+      config.ufo.interval_spread = -1.0;
+
+      assert_eq!(problems(&config), [
+         "player.speed must be greater than 0",
+         "enemies.bullet_speed must be greater than 0",
+         "ufo.interval must be greater than 0",
+         "ufo.interval_spread can't be negative"
+      ]);
+
+      // No spread at all is fine, the ufo just comes at a fixed interval
+      config = GameConfig::default();
+      config.ufo.interval_spread = 0.0;
+      assert_eq!(config.validate(), Ok(()));
+   }
+
+   #[test]
+   fn lives_must_fit_under_the_maximum() {
+      let mut config = GameConfig::default();
+      config.player.lives = 4;
+      config.player.max_lives = 3;
+      assert_eq!(problems(&config), ["player.lives must be between 1 and player.max_lives"]);
+
+      config.player.lives = 0;
+      assert_eq!(problems(&config), ["player.lives must be between 1 and player.max_lives"]);
+
+      config.player.lives = 3;
+      assert_eq!(config.validate(), Ok(()));
+   }
+
+   #[test]
+   fn the_formation_cant_be_empty_or_too_big() {
+      let mut config = GameConfig::default();
+      config.enemies.rows = 0;
+      assert_eq!(problems(&config), ["enemies.rows and enemies.per_row must be at least 1"]);
+
+      config.enemies.rows = 3;
+      config.enemies.per_row = 0;
+      assert_eq!(problems(&config), ["enemies.rows and enemies.per_row must be at least 1"]);
+
+      config.enemies.rows = 16;
+      config.enemies.per_row = 16;
+      assert_eq!(problems(&config), ["there can't be more than 255 enemies"]);
+   }
+
+   #[test]
+   fn the_formation_has_to_move_and_fire() {
+      let mut config = GameConfig::default();
+      config.enemies.starting_speed = 0;
+      config.enemies.small_volley = 0;
+      assert_eq!(problems(&config), [
+         "enemies.starting_speed must be at least 1",
+         "enemies.large_volley and enemies.small_volley must be at least 1"
+      ]);
+
+      config = GameConfig::default();
+      config.enemies.rows = 1;
+      config.enemies.per_row = 4;
+      assert_eq!(problems(&config), ["enemies.large_volley and enemies.small_volley can't be more than the number of enemies"]);
+   }
+
+   #[test]
+   fn load_reports_the_file_with_the_problems() {
+      let path = std::env::temp_dir().join(format!("space_invaders_config_{}.toml", std::process::id()));
+      fs::write(&path, "[player]\nlives = 9\nmax_lives = 5\n").unwrap();
+
+      let err = GameConfig::load(&path).unwrap_err();
+      fs::remove_file(&path).unwrap();
+
+      assert_eq!(err, format!("Invalid config {}: player.lives must be between 1 and player.max_lives", path.display()));
+   }
+}
@@ -0,0 +1,171 @@
+use ggez::glam::Vec2;
+use ggez::graphics::{self, Canvas, Color, DrawParam, Rect};
+use ggez::input::keyboard::KeyCode;
+
+use crate::game::Game;
+use crate::viewport::PLAYFIELD_WIDTH;
+
+const TEXT_SCALE: f32 = 28.0;
+const LINE_HEIGHT: f32 = 34.0;
+// Lines of history shown above the prompt
+const HISTORY_LINES: usize = 10;
+const TOGGLE_KEY: KeyCode = KeyCode::Grave;
+
+const HELP: &str = "wave N, lives N, god, kill_all, spawn ufo, speed N, seed N, give rapidfire";
+
+/*
+ * A drop-down developer console, only built with the dev feature. Opens
+ * with the backtick key and runs cheats on the game underneath, which
+ * keeps going meanwhile. Every command that worked is logged to the
+ * console and the game's cheat log, and a game with any in the log doesn't
+ * make it onto the high score table.
+ *
+ * Text uses ggez's built-in font, the game font has no punctuation.
+ */
+#[derive(Default)]
+pub struct Console {
+   open: bool,
+   input: String,
+   // Commands and what they did, newest last
+   history: Vec<String>
+}
+
+impl Console {
+   /*
+    * Returns whether the console used the key, in which case the game
+    * shouldn't see it.
+    */
+   pub fn key_down(&mut self, key: Option<KeyCode>, game: &mut Game) -> bool {
+      if key == Some(TOGGLE_KEY) {
+         self.open = !self.open;
+         return true;
+      }
+
+      if !self.open {
+         return false;
+      }
+
+      match key {
+         Some(KeyCode::Escape) => self.open = false,
+         Some(KeyCode::Back) => {
+            self.input.pop();
+         },
+         Some(KeyCode::Return) | Some(KeyCode::NumpadEnter) => {
+            let line = std::mem::take(&mut self.input);
+            self.submit(line.trim(), game);
+         },
+         _ => {}
+      }
+
+      true
+   }
+
+   pub fn type_char(&mut self, character: char) {
+      // The backtick that opened the console comes in as text too
+      if self.open && !character.is_control() && character != '`' {
+         self.input.push(character);
+      }
+   }
+
+   fn submit(&mut self, line: &str, game: &mut Game) {
+      if line.is_empty() {
+         return;
+      }
+
+      self.history.push(format!("> {}", line));
+
+      // Asking for help isn't cheating
+      if line == "help" {
+         self.history.push(String::from(HELP));
+         return;
+      }
+
+      match run(line, game) {
+         Ok(reply) => {
+            game.sim.cheats.log.push(String::from(line));
+            self.history.push(reply);
+         },
+         Err(err) => self.history.push(err)
+      }
+   }
+
+   pub fn draw(&self, canvas: &mut Canvas) {
+      if !self.open {
+         return;
+      }
+
+      let height = LINE_HEIGHT * (HISTORY_LINES + 1) as f32 + 20.0;
+      canvas.draw(&graphics::Quad, DrawParam::default()
+                  .dest_rect(Rect::new(0.0, 0.0, PLAYFIELD_WIDTH, height))
+                  .color(Color::new(0.0, 0.0, 0.0, 0.85)));
+
+      let shown = self.history.iter().rev().take(HISTORY_LINES).rev();
+      let prompt = format!("> {}_", self.input);
+
+      for (index, line) in shown.chain(std::iter::once(&prompt)).enumerate() {
+         let mut text = graphics::Text::new(line.as_str());
+         text.set_scale(graphics::PxScale::from(TEXT_SCALE));
+         canvas.draw(&text, DrawParam::default().dest(Vec2::new(20.0, 10.0 + index as f32 * LINE_HEIGHT)));
+      }
+   }
+}
+
+/*
+ * Runs one command, returning what it did or what's wrong with it.
+ */
+fn run(line: &str, game: &mut Game) -> Result<String, String> {
+   let mut words = line.split_whitespace();
+   let command = words.next().unwrap_or_default();
+   let argument = words.next();
+
+   if words.next().is_some() {
+      return Err(format!("too many arguments, try: {}", HELP));
+   }
+
+   let sim = &mut game.sim;
+
+   match (command, argument) {
+      ("wave", Some(wave)) => {
+         let wave = parse::<u32>(wave).filter(|wave| *wave >= 1).ok_or("wave takes a number from 1 up")?;
+         sim.jump_to_wave(wave);
+         Ok(format!("wave {}", wave))
+      },
+      ("lives", Some(lives)) => {
+         let lives = parse::<u8>(lives).filter(|lives| *lives >= 1).ok_or("lives takes a number from 1 to 255")?;
+         sim.state.set_player_lives(lives);
+         Ok(format!("{} lives", lives))
+      },
+      ("god", None) => {
+         sim.cheats.god_mode = !sim.cheats.god_mode;
+         Ok(format!("god mode {}", if sim.cheats.god_mode { "on" } else { "off" }))
+      },
+      ("kill_all", None) => {
+         sim.state.kill_all_enemies();
+         Ok(String::from("killed every invader"))
+      },
+      ("spawn", Some("ufo")) => {
+         let image_dimensions = sim.image_dimensions();
+         sim.state.spawn_ufo(PLAYFIELD_WIDTH, image_dimensions);
+         Ok(String::from("ufo on its way"))
+      },
+      ("speed", Some(speed)) => {
+         let speed = parse::<u8>(speed).ok_or("speed takes a number from 0 to 255")?;
+         sim.state.reset_enemy_movement_speed(speed);
+         Ok(format!("invader speed {}", speed))
+      },
+      ("seed", Some(seed)) => {
+         let seed = parse::<u64>(seed).ok_or("seed takes a number")?;
+         sim.state.reseed(seed);
+         Ok(format!("everything random comes from seed {} from now on", seed))
+      },
+      ("give", Some("rapidfire")) => {
+         sim.cheats.rapid_fire = true;
+         Ok(String::from("rapid fire, every shot brings the last bullet back"))
+      },
+      _ => Err(format!("unknown command, try: {}", HELP))
+   }
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Option<T> {
+   value.parse().ok()
+}
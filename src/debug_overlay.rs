@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use ggez::Context;
+use ggez::glam::Vec2;
+use ggez::graphics::{self, Canvas, Color, DrawMode, DrawParam, Drawable, Mesh, MeshBuilder, Rect};
+
+use crate::simulation::Simulation;
+use crate::state::{EntityInfo, EntityKind};
+use crate::viewport::{PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT};
+
+const TEXT_SCALE: f32 = 24.0;
+const LINE_WIDTH: f32 = 2.0;
+// Weight of the newest tick in the averaged tick time
+const TICK_TIME_SMOOTHING: f32 = 0.05;
+
+/*
+ * A developer overlay on top of every scene, for tuning collisions and
+ * movement: hitboxes, where bullets are headed, the formation edges that
+ * move_enemies turns around at and how long frames and ticks take.
+ * Clicking an entity pins a panel with its details.
+ *
+ * Text uses ggez's built-in font, the game font has no punctuation.
+ */
+#[derive(Default)]
+pub struct DebugOverlay {
+   visible: bool,
+   selected: Option<(EntityKind, usize)>,
+   // Averaged time a simulation tick takes, in milliseconds
+   tick_millis: f32
+}
+
+impl DebugOverlay {
+   pub fn toggle(&mut self) {
+      self.visible = !self.visible;
+   }
+
+   pub fn is_visible(&self) -> bool {
+      self.visible
+   }
+
+   pub fn record_tick(&mut self, duration: Duration) {
+      let millis = duration.as_secs_f32() * 1000.0;
+      self.tick_millis += (millis - self.tick_millis) * TICK_TIME_SMOOTHING;
+   }
+
+   /*
+    * Inspects whatever is at point on the playfield, or stops inspecting if
+    * there's nothing there.
+    */
+   pub fn select(&mut self, sim: &Simulation, point: Vec2) {
+      self.selected = sim.state.entity_at(point, sim.image_dimensions());
+   }
+
+   pub fn draw(&self, canvas: &mut Canvas, ctx: &mut Context, sim: &Simulation) {
+      if !self.visible {
+         return;
+      }
+
+      let image_dimensions = sim.image_dimensions();
+      let mut mesh = MeshBuilder::new();
+
+      for (kind, bounds) in sim.state.hitboxes(image_dimensions) {
+         mesh.rectangle(DrawMode::stroke(LINE_WIDTH), bounds, kind_color(kind)).ok();
+
+         // Bullets fly straight until they're off the playfield
+         let center = bounds.x + bounds.w / 2.0;
+         match kind {
+            EntityKind::PlayerBullet => {
+               mesh.line(&[Vec2::new(center, bounds.y), Vec2::new(center, 0.0)], 1.0, kind_color(kind)).ok();
+            },
+            EntityKind::EnemyBullet => {
+               mesh.line(&[Vec2::new(center, bounds.bottom()), Vec2::new(center, PLAYFIELD_HEIGHT)], 1.0, kind_color(kind)).ok();
+            },
+            _ => {}
+         }
+      }
+
+      let formation_color = Color::from_rgb(0, 200, 255);
+      let (left, right) = sim.state.formation_edges(image_dimensions);
+      for x in [left, right] {
+         mesh.line(&[Vec2::new(x, 0.0), Vec2::new(x, PLAYFIELD_HEIGHT)], LINE_WIDTH, formation_color).ok();
+      }
+
+      let selected = self.selected.and_then(|(kind, index)| sim.state.inspect(kind, index, sim.count, image_dimensions));
+      if let Some(bounds) = selected.as_ref().and_then(|info| info.bounds) {
+         mesh.rectangle(DrawMode::stroke(LINE_WIDTH * 2.0), bounds, Color::YELLOW).ok();
+      }
+
+      canvas.draw(&Mesh::from_data(ctx, mesh.build()), DrawParam::default());
+
+      draw_label(canvas, "formation", Vec2::new(left + 4.0, PLAYFIELD_HEIGHT / 4.0), formation_color);
+
+      let stats = format!("fps {:.0}\ntick {:.2} ms\ntick {}  wave {}",
+                          ctx.time.fps(), self.tick_millis, sim.count, sim.wave);
+      draw_panel(canvas, ctx, &stats, Vec2::new(10.0, PLAYFIELD_HEIGHT * 0.82));
+
+      match selected {
+         Some(info) => draw_panel(canvas, ctx, &describe(&info), Vec2::new(PLAYFIELD_WIDTH * 0.72, PLAYFIELD_HEIGHT * 0.3)),
+         None => draw_label(canvas, "click an entity to inspect it", Vec2::new(PLAYFIELD_WIDTH * 0.72, PLAYFIELD_HEIGHT * 0.3), Color::WHITE)
+      }
+   }
+}
+
+fn kind_color(kind: EntityKind) -> Color {
+   match kind {
+      EntityKind::Player => Color::GREEN,
+      EntityKind::Invader => Color::WHITE,
+      EntityKind::Ufo => Color::RED,
+      EntityKind::PlayerBullet => Color::YELLOW,
+      EntityKind::EnemyBullet => Color::MAGENTA
+   }
+}
+
+fn describe(info: &EntityInfo) -> String {
+   let mut lines = vec![
+      format!("{:?}", info.kind),
+      format!("dest_rect  x {:.1}  y {:.1}  w {:.2}  h {:.2}", info.dest_rect.x, info.dest_rect.y, info.dest_rect.w, info.dest_rect.h),
+      format!("get_scale  x {:.2}  y {:.2}", info.scale.x, info.scale.y),
+      format!("frame      {} of {}  ({:.4} x {:.4} of the sheet)", info.frame.0 + 1, info.frame.1, info.frame_size.y, info.frame_size.x)
+   ];
+
+   for (flag, value) in info.flags.iter() {
+      lines.push(format!("{:<18} {}", flag, value));
+   }
+
+   lines.join("\n")
+}
+
+fn text(text: &str) -> graphics::Text {
+   let mut text = graphics::Text::new(text);
+   text.set_scale(graphics::PxScale::from(TEXT_SCALE));
+   text
+}
+
+fn draw_label(canvas: &mut Canvas, label: &str, position: Vec2, color: Color) {
+   canvas.draw(&text(label), DrawParam::default().dest(position).color(color));
+}
+
+// Text on a dark background so it can be read over the sprites
+fn draw_panel(canvas: &mut Canvas, ctx: &mut Context, contents: &str, position: Vec2) {
+   let text = text(contents);
+   let dimensions = text.dimensions(ctx).unwrap_or_default();
+   let padding = 8.0;
+
+   canvas.draw(&graphics::Quad, DrawParam::default()
+               .dest_rect(Rect::new(position.x - padding, position.y - padding, dimensions.w + padding * 2.0, dimensions.h + padding * 2.0))
+               .color(Color::new(0.0, 0.0, 0.0, 0.75)));
+   canvas.draw(&text, DrawParam::default().dest(position).color(Color::WHITE));
+}
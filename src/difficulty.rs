@@ -0,0 +1,150 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+
+/*
+ * Presets applied on top of the gameplay config. Normal plays the config as
+ * it is, the others adjust the starting lives, how fast the invaders get
+ * from wave to wave, how many of them fire at once and how fast the bullets
+ * fly. There are no bunkers or power-ups in the game yet, so there's no
+ * bunker durability or drop rate for the presets to change.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+   Easy,
+   #[default]
+   Normal,
+   Hard,
+   // Fast and unforgiving, without any extra lives
+   Arcade
+}
+
+impl Difficulty {
+   pub fn name(&self) -> &'static str {
+      match self {
+         Difficulty::Easy => "easy",
+         Difficulty::Normal => "normal",
+         Difficulty::Hard => "hard",
+         Difficulty::Arcade => "arcade"
+      }
+   }
+
+   pub fn from_name(name: &str) -> Option<Difficulty> {
+      Difficulty::value_variants().iter().copied().find(|difficulty| difficulty.name() == name)
+   }
+
+   /*
+    * The next difficulty in the list for direction 1, the previous one for
+    * -1, wrapping around.
+    */
+   pub fn cycle(&self, direction: i8) -> Difficulty {
+      let all = Difficulty::value_variants();
+      let index = all.iter().position(|difficulty| difficulty == self).unwrap_or(0);
+
+      all[(index as isize + direction as isize).rem_euclid(all.len() as isize) as usize]
+   }
+
+   /*
+    * Adjusts the gameplay config for this difficulty.
+    */
+   pub fn apply(&self, config: &mut GameConfig) {
+      let num_enemies = config.num_enemies() as u16;
+      let player = &mut config.player;
+      let enemies = &mut config.enemies;
+
+      match self {
+         Difficulty::Easy => {
+            player.lives = player.lives.saturating_add(2);
+            player.bullet_speed *= 1.1;
+            enemies.starting_speed = enemies.starting_speed.saturating_sub(1);
+            enemies.bullet_speed *= 0.8;
+            enemies.all_fire_at = enemies.all_fire_at.saturating_sub(2);
+            enemies.large_volley = enemies.large_volley.saturating_sub(2);
+            enemies.small_volley = enemies.small_volley.saturating_sub(1);
+         },
+         Difficulty::Normal => {},
+         Difficulty::Hard => {
+            player.lives = player.lives.saturating_sub(1);
+            enemies.starting_speed = enemies.starting_speed.saturating_add(1);
+            enemies.bullet_speed *= 1.2;
+            enemies.all_fire_at += 2;
+            enemies.large_volley = enemies.large_volley.saturating_add(1);
+            enemies.small_volley = enemies.small_volley.saturating_add(1);
+         },
+         Difficulty::Arcade => {
+            player.bullet_speed *= 0.9;
+            enemies.starting_speed = enemies.starting_speed.saturating_add(1);
+            enemies.speed_per_wave = enemies.speed_per_wave.saturating_add(1);
+            enemies.bullet_speed *= 1.3;
+            enemies.all_fire_at += 3;
+            enemies.large_volley = enemies.large_volley.saturating_add(2);
+            enemies.small_volley = enemies.small_volley.saturating_add(2);
+            config.scoring.extra_life_at.clear();
+            config.scoring.extra_life_every = None;
+         }
+      }
+
+      // Keep whatever the config started with in the range the game runs with
+      player.lives = player.lives.clamp(1, player.max_lives);
+      enemies.starting_speed = enemies.starting_speed.max(1);
+      enemies.large_volley = enemies.large_volley.clamp(1, num_enemies.max(1));
+      enemies.small_volley = enemies.small_volley.clamp(1, num_enemies.max(1));
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn applied(difficulty: Difficulty, config: &GameConfig) -> GameConfig {
+      let mut config = config.clone();
+      difficulty.apply(&mut config);
+      config
+   }
+
+   #[test]
+   fn normal_plays_the_config_as_it_is() {
+      let config = GameConfig::default();
+      assert_eq!(applied(Difficulty::Normal, &config), config);
+   }
+
+   #[test]
+   fn presets_stay_in_the_range_the_game_runs_with() {
+      let mut config = GameConfig::default();
+      config.player.lives = 5;
+      config.enemies.starting_speed = 1;
+      config.enemies.small_volley = 1;
+
+      // Easy can't go past the maximum lives or below one invader moving and firing
+      let easy = applied(Difficulty::Easy, &config);
+      assert_eq!(easy.player.lives, config.player.max_lives);
+      assert_eq!(easy.enemies.starting_speed, 1);
+      assert_eq!(easy.enemies.large_volley, 3);
+      assert_eq!(easy.enemies.small_volley, 1);
+      assert_eq!(easy.validate(), Ok(()));
+
+      // Hard can't take the last life away
+      config.player.lives = 1;
+      assert_eq!(applied(Difficulty::Hard, &config).player.lives, 1);
+
+      // Nor have more invaders fire at once than there are
+      config.enemies.rows = 1;
+      config.enemies.per_row = 6;
+      let arcade = applied(Difficulty::Arcade, &config);
+      assert_eq!(arcade.enemies.large_volley, 6);
+      assert_eq!(arcade.enemies.small_volley, 3);
+      assert_eq!(arcade.validate(), Ok(()));
+   }
+
+   #[test]
+   fn arcade_has_no_extra_lives() {
+      let mut config = GameConfig::default();
+      config.scoring.extra_life_every = Some(5000);
+
+      let arcade = applied(Difficulty::Arcade, &config);
+      assert!(arcade.scoring.extra_life_at.is_empty());
+      assert_eq!(arcade.scoring.extra_life_every, None);
+   }
+}
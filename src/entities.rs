@@ -1,13 +1,41 @@
 #![allow(dead_code)]
 
-use ggez::{graphics::{Image, Canvas, Rect, DrawParam}, glam::Vec2, GameError};
+use ggez::{graphics::{Rect, DrawParam}, glam::Vec2, GameError};
 
-const PLAYER_NUM_LIVES: u8 = 3;
+use crate::render::Renderer;
+
+pub const PLAYER_NUM_LIVES: u8 = 3;
+pub const PLAYER_MAX_LIVES: u8 = 6;
+pub const UFO_POINTS: u64 = 100;
 
 
 // TODO!: Extract all common methods in a trait..
 // TODO!: Make an entity for the walls..
-// TODO!: Make an entity for the big ship that is worth a 100 points..
+
+/*
+ * Position between the previous and current simulation tick, used to draw
+ * smoothly when frames don't line up with ticks. An alpha of 0 is the
+ * previous position and 1 is the current one.
+ */
+fn interpolate(previous: Rect, current: Rect, alpha: f32) -> Rect {
+   Rect {
+      x: previous.x + (current.x - previous.x) * alpha,
+      y: previous.y + (current.y - previous.y) * alpha,
+      w: current.w,
+      h: current.h
+   }
+}
+
+/*
+ * The area an entity covers on the playfield, the way it's drawn. frame is
+ * (height, width) in fractions of the sprite sheet, like image_dimensions.
+ */
+fn bounds(dest_rect: Rect, frame: Vec2, image_dimensions: Vec2) -> Rect {
+   Rect::new(dest_rect.x,
+             dest_rect.y,
+             frame.y * image_dimensions.y * dest_rect.w,
+             frame.x * image_dimensions.x * dest_rect.h)
+}
 
 #[derive(Clone)]
 #[allow(dead_code)]
@@ -24,16 +52,26 @@ pub struct Sprite {
 impl Sprite {
    pub fn new(animation_frames: Vec<DrawParam>) -> Self {
       Sprite {
-         animation_frames: animation_frames
+         animation_frames
       }
    }
 
-   pub fn draw(&self, canvas: &mut Canvas, counter: u64, dest_rect: Rect, image: &Image) {
-      if self.animation_frames.len() == 0 {
-         canvas.draw(image, self.animation_frames[0].dest_rect(dest_rect));
+   pub fn draw(&self, renderer: &mut dyn Renderer, counter: u64, dest_rect: Rect) {
+      renderer.draw_sprite(self.animation_frames[self.frame_index(counter)].dest_rect(dest_rect));
+   }
+
+   /*
+    * The animation frame shown at counter and how many there are.
+    */
+   pub fn animation_frame(&self, counter: u64) -> (usize, usize) {
+      (self.frame_index(counter), self.animation_frames.len())
+   }
+
+   fn frame_index(&self, counter: u64) -> usize {
+      if self.animation_frames.is_empty() {
+         0
       } else {
-         let frame_index: usize = (counter as usize / 20) % self.animation_frames.len();
-         canvas.draw(image, self.animation_frames[frame_index].dest_rect(dest_rect));
+         (counter as usize / 20) % self.animation_frames.len()
       }
    }
 
@@ -50,14 +88,16 @@ impl Sprite {
 pub struct Bullet {
    sprite:    Sprite,
 	dest_rect:  Rect,
+   prev_dest_rect: Rect,
 	in_air:     bool
 }
 
 impl Bullet {
    pub fn new(sprite: Sprite, dest_rect: Rect) -> Self{
       Bullet {
-         sprite: sprite,
-         dest_rect: dest_rect,
+         sprite,
+         dest_rect,
+         prev_dest_rect: dest_rect,
          in_air: false
       }
    }
@@ -102,7 +142,7 @@ impl Bullet {
    }
 
    pub fn in_air(&self) -> bool {
-      return self.in_air;
+      self.in_air
    }
 
    pub fn get_frame_dimensions(&self) -> Result<Vec2, GameError> {
@@ -113,8 +153,28 @@ impl Bullet {
       Vec2 { x: self.dest_rect.h, y: self.dest_rect.w }
    }
 
-   pub fn draw(&self, canvas: &mut Canvas, counter: u64,image: &Image) {
-      self.sprite.draw(canvas, counter, self.dest_rect, image);
+   pub fn save_position(&mut self) {
+      self.prev_dest_rect = self.dest_rect;
+   }
+
+   pub fn set_sprite(&mut self, sprite: Sprite) {
+      self.sprite = sprite;
+   }
+
+   pub fn get_bounds(&self, image_dimensions: Vec2) -> Rect {
+      bounds(self.dest_rect, self.get_frame_dimensions().unwrap(), image_dimensions)
+   }
+
+   pub fn get_dest_rect(&self) -> Rect {
+      self.dest_rect
+   }
+
+   pub fn animation_frame(&self, counter: u64) -> (usize, usize) {
+      self.sprite.animation_frame(counter)
+   }
+
+   pub fn draw(&self, renderer: &mut dyn Renderer, counter: u64, alpha: f32) {
+      self.sprite.draw(renderer, counter, interpolate(self.prev_dest_rect, self.dest_rect, alpha));
    }
 }
 
@@ -124,27 +184,47 @@ pub struct Enemy {
 	sprite_death: Sprite,
 	bullet:      Bullet,
 	dest_rect:    Rect,
+   prev_dest_rect: Rect,
 	is_alive:     bool,
    death_animation_drawn: bool
 }
 
 impl Enemy {
    pub fn new(sprite_alive: Sprite, sprite_death: Sprite, bullet_sprite: Sprite, dest_rect: Rect) -> Self {
-      Enemy { sprite_alive: sprite_alive,
-              sprite_death: sprite_death,
-              bullet: Bullet { sprite: bullet_sprite, dest_rect: dest_rect.clone(), in_air: false},
-              dest_rect: dest_rect,
+      Enemy { sprite_alive,
+              sprite_death,
+              bullet: Bullet::new(bullet_sprite, dest_rect),
+              dest_rect,
+              prev_dest_rect: dest_rect,
               is_alive: true,
               death_animation_drawn: false }
    }
 
+   /*
+    * Swaps the sprites without touching anything else, e.g. when the
+    * sprite manifest is reloaded.
+    */
+   pub fn set_sprites(&mut self, sprite_alive: Sprite, sprite_death: Sprite, bullet_sprite: Sprite) {
+      self.sprite_alive = sprite_alive;
+      self.sprite_death = sprite_death;
+      self.bullet.set_sprite(bullet_sprite);
+   }
+
    pub fn shoot(&mut self) {
       self.bullet.set_in_air(true);
    }
 
-   pub fn update(&mut self, speed_y: f32, screen_height: f32, scaled: bool) {
+   pub fn save_position(&mut self) {
+      self.prev_dest_rect = self.dest_rect;
+      self.bullet.save_position();
+   }
+
+   /*
+    * Moves the bullet by speed_y units per second over dt seconds.
+    */
+   pub fn update(&mut self, speed_y: f32, dt: f32, screen_height: f32, scaled: bool) {
       if self.bullet.in_air() {
-         self.bullet.translate(Vec2 { x: 0.0, y: speed_y }, scaled);
+         self.bullet.translate(Vec2 { x: 0.0, y: speed_y * dt }, scaled);
 
          if self.bullet.get_coords().y > screen_height {
             self.bullet.set_in_air(false);
@@ -158,19 +238,19 @@ impl Enemy {
       }
    }
 
-   pub fn draw(&mut self, canvas: &mut Canvas, count: u64, image: &Image) {
+   pub fn draw(&mut self, renderer: &mut dyn Renderer, count: u64, alpha: f32) {
       if self.bullet.in_air() {
-        self.bullet.draw(canvas, count, image);
+        self.bullet.draw(renderer, count, alpha);
       }
 
       if self.is_alive {
-         self.sprite_alive.draw(canvas, count, self.dest_rect, image);
+         self.sprite_alive.draw(renderer, count, interpolate(self.prev_dest_rect, self.dest_rect, alpha));
       }
    }
 
-   pub fn draw_die(&mut self, canvas: &mut Canvas, count: u64, image: &Image) {
+   pub fn draw_die(&mut self, renderer: &mut dyn Renderer, count: u64, alpha: f32) {
       if !self.is_alive && !self.death_animation_drawn{
-         self.sprite_death.draw(canvas, count, self.dest_rect, image);
+         self.sprite_death.draw(renderer, count, interpolate(self.prev_dest_rect, self.dest_rect, alpha));
          self.death_animation_drawn = true;
       }
    }
@@ -200,6 +280,7 @@ impl Enemy {
 
    pub fn set_dest_rect(&mut self, rect: Rect) {
       self.dest_rect = rect;
+      self.prev_dest_rect = rect;
    }
 
    pub fn die(&mut self) {
@@ -214,6 +295,18 @@ impl Enemy {
       self.death_animation_drawn
    }
 
+   pub fn get_dest_rect(&self) -> Rect {
+      self.dest_rect
+   }
+
+   pub fn animation_frame(&self, counter: u64) -> (usize, usize) {
+      if self.is_alive { self.sprite_alive.animation_frame(counter) } else { self.sprite_death.animation_frame(counter) }
+   }
+
+   pub fn get_bullet(&self) -> &Bullet {
+      &self.bullet
+   }
+
    pub fn is_alive(&self) -> bool {
       self.is_alive
    }
@@ -230,6 +323,14 @@ impl Enemy {
       self.bullet.in_air()
    }
 
+   pub fn get_bounds(&self, image_dimensions: Vec2) -> Rect {
+      bounds(self.dest_rect, self.get_frame_dimensions().unwrap(), image_dimensions)
+   }
+
+   pub fn get_bullet_bounds(&self, image_dimensions: Vec2) -> Option<Rect> {
+      self.bullet.in_air().then(|| self.bullet.get_bounds(image_dimensions))
+   }
+
    pub fn bullet_collision_with_player(&self, player: &Player, image_dimensions: Vec2) -> bool {
       if self.bullet.in_air() {
          let bullet_coords = self.bullet.get_coords();
@@ -259,19 +360,126 @@ impl Enemy {
          //    }
          // }
 
-         if bullet_coords.y >= player_coords.y && bullet_coords.y + bullet_dim.x * image_dimensions.x * bullet_scale.x >= player_coords.y {
-            if (bullet_coords.x + 10.0 >= player_coords.x &&
+         if bullet_coords.y >= player_coords.y && bullet_coords.y + bullet_dim.x * image_dimensions.x * bullet_scale.x >= player_coords.y
+            && ((bullet_coords.x + 10.0 >= player_coords.x &&
                bullet_coords.x + 10.0 <= player_coords.x + player_dim.y * image_dimensions.y * player_scaling.y - 30.0)
                ||
                (bullet_coords.x + bullet_dim.y * image_dimensions.y * bullet_scale.y >= player_coords.x &&
-                bullet_coords.x + bullet_dim.y * image_dimensions.y * bullet_scale.y <= player_coords.x + player_dim.y * image_dimensions.y * player_scaling.y - 30.0)
-            {
-               return true;
-            }
+                bullet_coords.x + bullet_dim.y * image_dimensions.y * bullet_scale.y <= player_coords.x + player_dim.y * image_dimensions.y * player_scaling.y - 30.0))
+         {
+            return true;
          }
       }
 
-      return false;
+      false
+   }
+}
+
+/*
+ * The flying saucer that now and then crosses the screen above the
+ * formation. Worth UFO_POINTS when shot.
+ */
+#[derive(Clone)]
+pub struct Ufo {
+   sprite:         Sprite,
+   dest_rect:      Rect,
+   prev_dest_rect: Rect,
+   // -1 when flying left, 1 when flying right
+   direction:      f32,
+   is_active:      bool
+}
+
+impl Ufo {
+   pub fn new(sprite: Sprite, dest_rect: Rect) -> Self {
+      Ufo {
+         sprite,
+         dest_rect,
+         prev_dest_rect: dest_rect,
+         direction: 1.0,
+         is_active: false
+      }
+   }
+
+   pub fn set_sprite(&mut self, sprite: Sprite) {
+      self.sprite = sprite;
+   }
+
+   /*
+    * Starts a flight from just outside the left or right edge.
+    */
+   pub fn spawn(&mut self, from_left: bool, screen_width: f32, image_dimensions: Vec2) {
+      let width = self.get_width(image_dimensions);
+
+      self.direction = if from_left { 1.0 } else { -1.0 };
+      self.dest_rect.x = if from_left { -width } else { screen_width };
+      self.prev_dest_rect = self.dest_rect;
+      self.is_active = true;
+   }
+
+   pub fn save_position(&mut self) {
+      self.prev_dest_rect = self.dest_rect;
+   }
+
+   /*
+    * Moves the ufo by speed units per second over dt seconds. Returns true
+    * when it has left the screen on the other side.
+    */
+   pub fn update(&mut self, speed: f32, dt: f32, screen_width: f32, image_dimensions: Vec2) -> bool {
+      if !self.is_active {
+         return false;
+      }
+
+      self.dest_rect.translate(Vec2::new(self.direction * speed * dt, 0.0));
+
+      let width = self.get_width(image_dimensions);
+      if self.dest_rect.x > screen_width || self.dest_rect.x + width < 0.0 {
+         self.is_active = false;
+         return true;
+      }
+
+      false
+   }
+
+   pub fn draw(&self, renderer: &mut dyn Renderer, count: u64, alpha: f32) {
+      if self.is_active {
+         self.sprite.draw(renderer, count, interpolate(self.prev_dest_rect, self.dest_rect, alpha));
+      }
+   }
+
+   pub fn is_active(&self) -> bool {
+      self.is_active
+   }
+
+   pub fn remove(&mut self) {
+      self.is_active = false;
+   }
+
+   pub fn get_coords(&self) -> Vec2 {
+      Vec2 { x: self.dest_rect.x, y: self.dest_rect.y }
+   }
+
+   pub fn get_scale(&self) -> Vec2 {
+      Vec2 { x: self.dest_rect.h, y: self.dest_rect.w }
+   }
+
+   pub fn get_frame_dimensions(&self) -> Result<Vec2, GameError> {
+      self.sprite.get_frame_dimensions(0)
+   }
+
+   pub fn get_bounds(&self, image_dimensions: Vec2) -> Rect {
+      bounds(self.dest_rect, self.get_frame_dimensions().unwrap(), image_dimensions)
+   }
+
+   pub fn get_dest_rect(&self) -> Rect {
+      self.dest_rect
+   }
+
+   pub fn animation_frame(&self, counter: u64) -> (usize, usize) {
+      self.sprite.animation_frame(counter)
+   }
+
+   fn get_width(&self, image_dimensions: Vec2) -> f32 {
+      self.get_frame_dimensions().unwrap().y * image_dimensions.y * self.dest_rect.w
    }
 }
 
@@ -281,35 +489,63 @@ pub struct Player {
 	sprite_death: Sprite,
 	bullet:      Bullet,
 	dest_rect:    Rect,
+   prev_dest_rect: Rect,
 	is_alive:     bool,
-	lives:        u8
+	lives:        u8,
+   starting_lives: u8,
+   max_lives:    u8
 }
 
 impl Player {
    pub fn new(sprite_alive: Sprite, sprite_death: Sprite, bullet_sprite: Sprite, dest_rect: Rect) -> Self {
-      let mut dest_rect_bullet = dest_rect.clone();
+      let mut dest_rect_bullet = dest_rect;
       dest_rect_bullet.w /= 2.0;
       dest_rect_bullet.h /= 2.0;
 
 
-      Player { sprite_alive: sprite_alive,
-               sprite_death: sprite_death,
-               bullet: Bullet { sprite: bullet_sprite, dest_rect: dest_rect_bullet, in_air: false},
-               dest_rect: dest_rect,
+      Player { sprite_alive,
+               sprite_death,
+               bullet: Bullet::new(bullet_sprite, dest_rect_bullet),
+               dest_rect,
+               prev_dest_rect: dest_rect,
                is_alive: true,
-               lives: PLAYER_NUM_LIVES}
+               lives: PLAYER_NUM_LIVES,
+               starting_lives: PLAYER_NUM_LIVES,
+               max_lives: PLAYER_MAX_LIVES}
+   }
+
+   pub fn set_sprites(&mut self, sprite_alive: Sprite, sprite_death: Sprite, bullet_sprite: Sprite) {
+      self.sprite_alive = sprite_alive;
+      self.sprite_death = sprite_death;
+      self.bullet.set_sprite(bullet_sprite);
+   }
+
+   /*
+    * Sets how many lives the player gets on (re)spawn and the most it can
+    * hold through extra life awards. Also resets the current lives.
+    */
+   pub fn configure_lives(&mut self, starting_lives: u8, max_lives: u8) {
+      self.starting_lives = starting_lives;
+      self.max_lives = max_lives.max(starting_lives);
+      self.lives = starting_lives;
    }
 
    pub fn shoot(&mut self) {
       self.bullet.set_in_air(true);
    }
 
+   pub fn save_position(&mut self) {
+      self.prev_dest_rect = self.dest_rect;
+      self.bullet.save_position();
+   }
+
    /*
     * TODO!. Refer to the translate method for the explanation of scaled.
+    * Moves the bullet by speed_y units per second over dt seconds.
     */
-   pub fn update(&mut self, speed_y: f32, scaled: bool) {
+   pub fn update(&mut self, speed_y: f32, dt: f32, scaled: bool) {
       if self.bullet.in_air() {
-         self.bullet.translate(Vec2 { x: 0.0, y: -speed_y }, scaled);
+         self.bullet.translate(Vec2 { x: 0.0, y: -speed_y * dt }, scaled);
 
          if self.bullet.get_coords().y < 0.0 {
             self.bullet.set_in_air(false);
@@ -323,13 +559,13 @@ impl Player {
       }
    }
 
-   pub fn draw(&mut self, canvas: &mut Canvas, count: u64, image: &Image) {
+   pub fn draw(&mut self, renderer: &mut dyn Renderer, count: u64, alpha: f32) {
       if self.bullet.in_air() {
-        self.bullet.draw(canvas, count, image);
+        self.bullet.draw(renderer, count, alpha);
       }
 
       if self.is_alive {
-         self.sprite_alive.draw(canvas, count, self.dest_rect, image);
+         self.sprite_alive.draw(renderer, count, interpolate(self.prev_dest_rect, self.dest_rect, alpha));
       }
    }
 
@@ -337,9 +573,9 @@ impl Player {
       self.is_alive
    }
 
-   pub fn draw_die(&mut self, canvas: &mut Canvas, count: u64, image: &Image) {
+   pub fn draw_die(&mut self, renderer: &mut dyn Renderer, count: u64, alpha: f32) {
       if !self.is_alive {
-         self.sprite_death.draw(canvas, count, self.dest_rect, image);
+         self.sprite_death.draw(renderer, count, interpolate(self.prev_dest_rect, self.dest_rect, alpha));
       }
    }
 
@@ -361,6 +597,18 @@ impl Player {
       self.lives
    }
 
+   pub fn get_dest_rect(&self) -> Rect {
+      self.dest_rect
+   }
+
+   pub fn animation_frame(&self, counter: u64) -> (usize, usize) {
+      if self.is_alive { self.sprite_alive.animation_frame(counter) } else { self.sprite_death.animation_frame(counter) }
+   }
+
+   pub fn get_bullet(&self) -> &Bullet {
+      &self.bullet
+   }
+
    pub fn get_scale(&self) -> Vec2 {
       Vec2 { x: self.dest_rect.h, y: self.dest_rect.w }
    }
@@ -374,35 +622,67 @@ impl Player {
    }
 
    pub fn bullet_collision_with_enemy(&self, enemy: &Enemy, image_dimensions: Vec2) -> bool {
+      self.bullet_collision(enemy.get_coords(), enemy.get_frame_dimensions().unwrap(), enemy.get_scale(), image_dimensions)
+   }
+
+   pub fn bullet_collision_with_ufo(&self, ufo: &Ufo, image_dimensions: Vec2) -> bool {
+      ufo.is_active() && self.bullet_collision(ufo.get_coords(), ufo.get_frame_dimensions().unwrap(), ufo.get_scale(), image_dimensions)
+   }
+
+   fn bullet_collision(&self, target_coords: Vec2, target_dim: Vec2, target_scaling: Vec2, image_dimensions: Vec2) -> bool {
       if self.bullet.in_air {
          let bullet_coords = self.bullet.get_coords();
          let bullet_dim = self.bullet.get_frame_dimensions().unwrap();
          let bullet_scale = self.bullet.get_scale();
 
-         let enemy_coords = enemy.get_coords();
-         let enemy_dim = enemy.get_frame_dimensions().unwrap();
-         let enemy_scaling = enemy.get_scale();
-
-         if bullet_coords.y <= enemy_coords.y + enemy_dim.x * image_dimensions.x * enemy_scaling.x &&
-            bullet_coords.y * bullet_dim.x * image_dimensions.x * bullet_scale.x >= enemy_coords.y + enemy_dim.x * image_dimensions.x * enemy_scaling.x {
-            if (bullet_coords.x + 10.0 >= enemy_coords.x &&
-               bullet_coords.x + 10.0 <= enemy_coords.x + enemy_dim.y * image_dimensions.y * enemy_scaling.y - 30.0)
+         if bullet_coords.y <= target_coords.y + target_dim.x * image_dimensions.x * target_scaling.x &&
+            bullet_coords.y * bullet_dim.x * image_dimensions.x * bullet_scale.x >= target_coords.y + target_dim.x * image_dimensions.x * target_scaling.x
+            && ((bullet_coords.x + 10.0 >= target_coords.x &&
+               bullet_coords.x + 10.0 <= target_coords.x + target_dim.y * image_dimensions.y * target_scaling.y - 30.0)
                ||
-               (bullet_coords.x + bullet_dim.y * image_dimensions.y * bullet_scale.y >= enemy_coords.x &&
-                bullet_coords.x + bullet_dim.y * image_dimensions.y * bullet_scale.y <= enemy_coords.x + enemy_dim.y * image_dimensions.y * enemy_scaling.y - 30.0)
-            {
-               return true;
-            }
+               (bullet_coords.x + bullet_dim.y * image_dimensions.y * bullet_scale.y >= target_coords.x &&
+                bullet_coords.x + bullet_dim.y * image_dimensions.y * bullet_scale.y <= target_coords.x + target_dim.y * image_dimensions.y * target_scaling.y - 30.0))
+         {
+            return true;
          }
       }
 
-      return false;
+      false
+   }
+
+   pub fn is_bullet_in_air(&self) -> bool {
+      self.bullet.in_air()
    }
 
    pub fn set_bullet_in_air(&mut self, in_air: bool) {
       self.bullet.set_in_air(in_air);
    }
 
+   pub fn get_bounds(&self, image_dimensions: Vec2) -> Rect {
+      bounds(self.dest_rect, self.get_frame_dimensions().unwrap(), image_dimensions)
+   }
+
+   pub fn get_bullet_bounds(&self, image_dimensions: Vec2) -> Option<Rect> {
+      self.bullet.in_air().then(|| self.bullet.get_bounds(image_dimensions))
+   }
+
+   pub fn reload_bullet(&mut self) {
+      self.bullet.set_in_air(false);
+      self.bullet.set_bullet_coords(self.get_coords());
+   }
+
+   /*
+    * Any number of lives, past the cap too. No lives at all is game over.
+    */
+   #[cfg(feature = "dev")]
+   pub fn set_lives(&mut self, lives: u8) {
+      self.lives = lives;
+
+      if self.lives == 0 {
+         self.die();
+      }
+   }
+
    pub fn lose_life(&mut self) {
       self.lives -= 1;
 
@@ -411,10 +691,24 @@ impl Player {
       }
    }
 
+   /*
+    * Adds a life unless the player is already at the cap. Returns whether
+    * a life was actually awarded.
+    */
+   pub fn gain_life(&mut self) -> bool {
+      if !self.is_alive || self.lives >= self.max_lives {
+         return false;
+      }
+
+      self.lives += 1;
+      true
+   }
+
    pub fn revive(&mut self, position: Vec2) {
       self.is_alive = true;
-      self.lives = PLAYER_NUM_LIVES;
+      self.lives = self.starting_lives;
       self.dest_rect.x = position.x;
       self.dest_rect.y = position.y;
+      self.prev_dest_rect = self.dest_rect;
    }
 }
\ No newline at end of file
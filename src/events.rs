@@ -0,0 +1,128 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/*
+ * The two kinds of invaders in the formation, after the sprites they use.
+ * Rows alternate between them, starting with a.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvaderKind {
+   A,
+   B
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameOverReason {
+   // The last life was shot away
+   OutOfLives,
+   // The player restarted or went back to the title in the middle of a game
+   Quit
+}
+
+/*
+ * Things that happen in the simulation which the rest of the game (sound,
+ * effects, statistics) may want to react to. The simulation only records
+ * them, it never reacts itself.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "event")]
+pub enum GameEvent {
+   // Comes with the first tick of a game
+   GameStarted { wave: u32 },
+   ShotFired,
+   InvaderKilled { kind: InvaderKind, points: u64 },
+   // Every hit costs a life, LifeLost follows right after
+   PlayerHit,
+   LifeLost { lives_left: u8 },
+   // Sent before the next wave is loaded, wave is the one that was cleared
+   WaveCleared { wave: u32 },
+   UfoSpawned,
+   UfoLeft,
+   UfoKilled { points: u64 },
+   ExtraLife,
+   GameOver { reason: GameOverReason }
+}
+
+/*
+ * Something that reacts to the simulation's events, e.g. the sound. tick is
+ * the simulation tick the event happened on, counted from the start of the
+ * game.
+ */
+pub trait Subscriber {
+   fn notify(&mut self, tick: u64, event: &GameEvent);
+}
+
+// Lets optional subscribers, like an event log that wasn't asked for, sit
+// on the bus with the others
+impl<T: Subscriber> Subscriber for Option<T> {
+   fn notify(&mut self, tick: u64, event: &GameEvent) {
+      if let Some(subscriber) = self {
+         subscriber.notify(tick, event);
+      }
+   }
+}
+
+/*
+ * The event bus: hands each event to every subscriber, in the order they're
+ * listed. The simulation queues its events while it ticks, whoever drives
+ * it drains them once per tick and publishes them here.
+ */
+pub fn publish(tick: u64, events: &[GameEvent], subscribers: &mut [&mut dyn Subscriber]) {
+   for event in events {
+      for subscriber in subscribers.iter_mut() {
+         subscriber.notify(tick, event);
+      }
+   }
+}
+
+#[derive(Serialize)]
+struct Record<'a> {
+   tick: u64,
+   #[serde(flatten)]
+   event: &'a GameEvent
+}
+
+/*
+ * Writes every event to a file as JSON Lines, one object per event with
+ * its tick, e.g. {"tick":312,"event":"InvaderKilled","kind":"a","points":10}.
+ * Ticks start over with every game, GameOver ends one. If writing fails the
+ * log says so once and stops.
+ */
+pub struct EventLog {
+   path: PathBuf,
+   writer: Option<BufWriter<File>>
+}
+
+impl EventLog {
+   pub fn create(path: &Path) -> io::Result<Self> {
+      Ok(EventLog {
+         path: PathBuf::from(path),
+         writer: Some(BufWriter::new(File::create(path)?))
+      })
+   }
+
+   fn write(&mut self, tick: u64, event: &GameEvent) -> io::Result<()> {
+      if let Some(writer) = self.writer.as_mut() {
+         serde_json::to_writer(&mut *writer, &Record { tick, event })?;
+         writer.write_all(b"\n")?;
+         // Keeps the file readable while the game is still running
+         writer.flush()?;
+      }
+
+      Ok(())
+   }
+}
+
+impl Subscriber for EventLog {
+   fn notify(&mut self, tick: u64, event: &GameEvent) {
+      if let Err(err) = self.write(tick, event) {
+         eprintln!("Failed to write to event log {}, not logging any more events: {}", self.path.display(), err);
+         self.writer = None;
+      }
+   }
+}
@@ -0,0 +1,279 @@
+use std::path::{Path, PathBuf};
+
+use ggez::Context;
+use ggez::glam::Vec2;
+use ggez::conf::FullscreenType;
+use ggez::graphics::{Canvas, Image};
+
+use crate::achievements::Achievements;
+use crate::gamepad;
+use crate::input::{self, Action, InputEvent, InputState};
+use crate::input_source::{InputSource, LiveSource, ReplayRecorder};
+use crate::audio::{Audio, AudioSettings};
+use crate::config::GameConfig;
+use crate::difficulty::Difficulty;
+use crate::events::{self, EventLog, Subscriber};
+use crate::highscores::HighScoreTable;
+use crate::render::{self, GpuRenderer};
+use crate::hot_reload::FileWatcher;
+use crate::settings::UserSettings;
+use crate::simulation::{GameOptions, Simulation};
+use crate::statistics::UserStatistics;
+use crate::sprites::SpriteManifest;
+use crate::toast::Toasts;
+
+// Longest error shown in a toast, the whole error goes to the console
+const RELOAD_ERROR_LENGTH: usize = 60;
+
+// What the game keeps in the data directory
+pub const SCORE_FILE: &str = ".high_score.txt";
+pub const SETTINGS_FILE: &str = "settings.toml";
+const STATS_FILE: &str = "statistics.toml";
+const ACHIEVEMENTS_FILE: &str = "achievements.toml";
+
+/*
+ * Everything that outlives a single scene: the simulation, the score and
+ * resources shared by all screens.
+ */
+pub struct Game {
+   pub sim: Simulation,
+   pub high_scores: HighScoreTable,
+   pub sprite_sheet: Image,
+   pub input: InputState,
+   // Where the simulation gets the player's input from
+   pub input_source: Box<dyn InputSource>,
+   pub settings: UserSettings,
+   pub stats: UserStatistics,
+   pub achievements: Achievements,
+   pub toasts: Toasts,
+   pub audio: Audio,
+   pub fullscreen: bool,
+   // Every game uses this seed if set, otherwise a new random one
+   pub seed: Option<u64>,
+   // Each game is recorded to this replay file if set
+   pub record_path: Option<PathBuf>,
+   // Every event of every game is written here if set
+   pub event_log: Option<EventLog>,
+   // The gameplay config before the difficulty is applied
+   config: GameConfig,
+   sprites: SpriteManifest,
+   config_path: Option<PathBuf>,
+   sprites_path: Option<PathBuf>,
+   watcher: FileWatcher
+}
+
+impl Game {
+   /*
+    * The high scores, settings, statistics and achievements are loaded
+    * from and saved to data_dir.
+    */
+   pub fn new(sprite_sheet: Image, data_dir: &Path, config: GameConfig, sprites: SpriteManifest, options: GameOptions) -> Self {
+      let image_dimensions = Vec2 { x: sprite_sheet.height() as f32, y: sprite_sheet.width() as f32 };
+      let settings = UserSettings::load(&data_dir.join(SETTINGS_FILE));
+      let mut audio = Audio::new();
+      audio.apply_settings(&settings.audio);
+
+      Game {
+         sim: Simulation::new(image_dimensions, config.clone(), sprites.clone(), options),
+         high_scores: HighScoreTable::load(&data_dir.join(SCORE_FILE).to_string_lossy()),
+         sprite_sheet,
+         input: InputState::default(),
+         input_source: Box::new(LiveSource),
+         settings,
+         stats: UserStatistics::load(&data_dir.join(STATS_FILE)),
+         achievements: Achievements::load(&data_dir.join(ACHIEVEMENTS_FILE)),
+         toasts: Toasts::default(),
+         audio,
+         fullscreen: false,
+         seed: None,
+         record_path: None,
+         event_log: None,
+         config,
+         sprites,
+         config_path: None,
+         sprites_path: None,
+         watcher: FileWatcher::default()
+      }
+   }
+
+   fn image_dimensions(&self) -> Vec2 {
+      Vec2 { x: self.sprite_sheet.height() as f32, y: self.sprite_sheet.width() as f32 }
+   }
+
+   /*
+    * Starts over with another difficulty, from the next reset on.
+    */
+   pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+      let options = GameOptions { difficulty, ..self.sim.options.clone() };
+      self.sim = Simulation::new(self.image_dimensions(), self.config.clone(), self.sprites.clone(), options);
+   }
+
+   /*
+    * Puts everything back to how it is at the start of a game.
+    */
+   pub fn reset(&mut self) {
+      let seed = self.seed.unwrap_or_else(rand::random);
+      self.sim.reset(seed);
+      self.audio.stop_loops();
+      self.audio.reset_march();
+
+      if let Some(path) = self.record_path.as_ref() {
+         let headers = [
+            ("seed", seed.to_string()),
+            ("difficulty", String::from(self.sim.options.difficulty.name())),
+            ("start_wave", self.sim.options.start_wave.to_string())
+         ];
+
+         match ReplayRecorder::create(path, Box::new(LiveSource), &headers) {
+            Ok(recorder) => self.input_source = Box::new(recorder),
+            Err(err) => eprintln!("Failed to create replay file {}: {}", path.display(), err)
+         }
+      }
+   }
+
+   /*
+    * Starts a game for the attract mode demo. It's never recorded and always
+    * gets a new seed, so the demo doesn't play the same game every time.
+    */
+   pub fn reset_demo(&mut self) {
+      self.sim.reset(rand::random());
+      self.audio.stop_loops();
+      self.audio.reset_march();
+   }
+
+   /*
+    * Applies changes to the gameplay config and the sprite manifest while
+    * the game is running. Files that don't exist yet are picked up once
+    * they're created.
+    */
+   pub fn watch_files(&mut self, config_path: &Path, sprites_path: &Path) {
+      self.watcher.watch(config_path);
+      self.watcher.watch(sprites_path);
+      self.config_path = Some(PathBuf::from(config_path));
+      self.sprites_path = Some(PathBuf::from(sprites_path));
+   }
+
+   /*
+    * Reloads whatever changed. A file with errors is reported and ignored,
+    * the game keeps going with what it had.
+    */
+   pub fn reload_changed_files(&mut self, dt: f32) {
+      for path in self.watcher.poll(dt) {
+         if self.config_path.as_ref() == Some(&path) {
+            match GameConfig::load(&path) {
+               Ok(config) => {
+                  self.sim.set_config(config.clone());
+                  self.config = config;
+                  self.toasts.push("CONFIG RELOADED");
+               },
+               Err(err) => self.report_reload_error("CONFIG", &err)
+            }
+         } else if self.sprites_path.as_ref() == Some(&path) {
+            match SpriteManifest::load(&path) {
+               Ok(sprites) => {
+                  self.sim.set_sprites(sprites.clone());
+                  self.sprites = sprites;
+                  self.toasts.push("SPRITES RELOADED");
+               },
+               Err(err) => self.report_reload_error("SPRITES", &err)
+            }
+         }
+      }
+   }
+
+   fn report_reload_error(&mut self, what: &str, err: &str) {
+      eprintln!("{}", err);
+
+      // Without the "Invalid config <path>" in front, the toast above says as much
+      let detail = err.split_once(": ").map(|(_, detail)| detail).unwrap_or(err);
+      let detail: String = detail.chars().take(RELOAD_ERROR_LENGTH).collect();
+
+      self.toasts.push(&format!("{}  NOT  RELOADED", what));
+      self.toasts.push(&detail);
+   }
+
+   /*
+    * Passes what happened in the simulation on to everything that reacts to it.
+    */
+   pub fn dispatch_events(&mut self) {
+      let events = self.sim.drain_events();
+      let mut subscribers: Vec<&mut dyn Subscriber> = vec![&mut self.audio, &mut self.event_log];
+
+      // Like a high score, statistics and achievements only count games
+      // played without cheats
+      if !self.sim.cheats.used() {
+         subscribers.push(&mut self.stats);
+         subscribers.push(&mut self.achievements);
+      }
+
+      events::publish(self.sim.count, &events, &mut subscribers);
+
+      for achievement in self.achievements.take_unlocked() {
+         self.toasts.push(&format!("ACHIEVEMENT  UNLOCKED  {}", achievement.name()));
+      }
+   }
+
+   /*
+    * Gives up on the game in progress, e.g. to restart it or to go back to
+    * the title.
+    */
+   pub fn quit(&mut self) {
+      self.sim.quit();
+      self.dispatch_events();
+   }
+
+   pub fn handle_input(&mut self, event: InputEvent) {
+      self.input.handle(event, &self.settings.controls, &self.settings.gamepad);
+   }
+
+   /*
+    * The name of the first key or button bound to action, for on-screen
+    * prompts. Buttons are shown while a gamepad is connected.
+    */
+   pub fn prompt(&self, action: Action) -> String {
+      if self.input.has_gamepad() {
+         if let Some(button) = self.settings.gamepad.buttons.buttons(action).first() {
+            return gamepad::button_label(*button);
+         }
+      }
+
+      match self.settings.controls.keys(action).first() {
+         Some(key) => input::key_label(*key),
+         None => String::from("---")
+      }
+   }
+
+   /*
+    * Applies and saves a change to the audio settings.
+    */
+   pub fn update_audio_settings(&mut self, change: impl FnOnce(&mut AudioSettings)) {
+      change(&mut self.settings.audio);
+      self.audio.apply_settings(&self.settings.audio);
+      self.settings.save();
+   }
+
+   pub fn toggle_mute(&mut self) {
+      self.update_audio_settings(|audio| audio.muted = !audio.muted);
+      self.toasts.push(if self.settings.audio.muted { "SOUND  OFF" } else { "SOUND  ON" });
+   }
+
+   pub fn toggle_fullscreen(&mut self, ctx: &mut Context) {
+      let fullscreen_type = if self.fullscreen { FullscreenType::Windowed } else { FullscreenType::Desktop };
+
+      if let Err(err) = ctx.gfx.set_fullscreen(fullscreen_type) {
+         eprintln!("Failed to switch fullscreen mode: {}", err);
+      } else {
+         self.fullscreen = !self.fullscreen;
+      }
+   }
+
+   pub fn draw_playfield(&mut self, canvas: &mut Canvas, ctx: &mut Context, alpha: f32) {
+      let mut renderer = GpuRenderer::new(canvas, ctx).with_sprite_sheet(&self.sprite_sheet);
+      render::draw_playfield(&mut renderer, &mut self.sim, alpha);
+   }
+
+   pub fn draw_hud(&mut self, canvas: &mut Canvas, ctx: &mut Context) {
+      let high_score = self.high_scores.best(self.sim.options.difficulty);
+      render::draw_hud(&mut GpuRenderer::new(canvas, ctx), &self.sim, high_score);
+   }
+}
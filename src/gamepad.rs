@@ -0,0 +1,363 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+use ggez::Context;
+use ggez::event::{Axis, Button, GamepadId};
+use serde::{Deserialize, Serialize};
+
+use crate::input::{Action, InputEvent};
+
+pub const DEFAULT_DEADZONE: f32 = 0.25;
+
+// Every button that can be bound, in the order they're listed in.
+const BUTTONS: [Button; 19] = [
+   Button::South, Button::East, Button::North, Button::West, Button::C, Button::Z,
+   Button::LeftTrigger, Button::LeftTrigger2, Button::RightTrigger, Button::RightTrigger2,
+   Button::Select, Button::Start, Button::Mode, Button::LeftThumb, Button::RightThumb,
+   Button::DPadUp, Button::DPadDown, Button::DPadLeft, Button::DPadRight
+];
+
+/*
+ * Gamepad half of the action map. The left stick always moves and navigates
+ * menus, the buttons are rebindable. Stored in the settings file as
+ *
+ *    [gamepad]
+ *    deadzone = 0.25
+ *
+ *    [gamepad.buttons]
+ *    fire = ["South"]
+ */
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GamepadMap {
+   pub deadzone: f32,
+   pub buttons: ButtonBindings
+}
+
+impl Default for GamepadMap {
+   fn default() -> Self {
+      GamepadMap {
+         deadzone: DEFAULT_DEADZONE,
+         buttons: ButtonBindings::default()
+      }
+   }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, Vec<String>>", into = "BTreeMap<String, Vec<String>>")]
+pub struct ButtonBindings {
+   bindings: BTreeMap<Action, Vec<Button>>
+}
+
+impl Default for ButtonBindings {
+   fn default() -> Self {
+      let mut bindings = BTreeMap::new();
+
+      bindings.insert(Action::MoveLeft, vec![Button::DPadLeft]);
+      bindings.insert(Action::MoveRight, vec![Button::DPadRight]);
+      bindings.insert(Action::Fire, vec![Button::South, Button::West]);
+      bindings.insert(Action::Pause, vec![Button::Start]);
+      bindings.insert(Action::Confirm, vec![Button::South, Button::Start]);
+      bindings.insert(Action::Back, vec![Button::East, Button::Select]);
+      bindings.insert(Action::MenuUp, vec![Button::DPadUp]);
+      bindings.insert(Action::MenuDown, vec![Button::DPadDown]);
+
+      ButtonBindings { bindings }
+   }
+}
+
+impl From<BTreeMap<String, Vec<String>>> for ButtonBindings {
+   // Same rules as the keyboard bindings: missing actions keep their
+   // defaults, unknown names are reported and skipped.
+   fn from(bindings: BTreeMap<String, Vec<String>>) -> Self {
+      let mut map = ButtonBindings::default();
+
+      for (name, button_names) in bindings {
+         let action = match Action::from_name(&name) {
+            Some(action) => action,
+            None => {
+               eprintln!("Unknown action in gamepad bindings: {}", name);
+               continue;
+            }
+         };
+
+         let buttons = button_names.iter().filter_map(|button_name| {
+            let button = BUTTONS.iter().copied().find(|button| format!("{:?}", button) == *button_name);
+
+            if button.is_none() {
+               eprintln!("Unknown gamepad button {} bound to {}", button_name, name);
+            }
+
+            button
+         }).collect();
+
+         map.bindings.insert(action, buttons);
+      }
+
+      map
+   }
+}
+
+impl From<ButtonBindings> for BTreeMap<String, Vec<String>> {
+   fn from(map: ButtonBindings) -> Self {
+      map.bindings.iter()
+                  .map(|(action, buttons)| (String::from(action.name()), buttons.iter().map(|button| format!("{:?}", button)).collect()))
+                  .collect()
+   }
+}
+
+impl ButtonBindings {
+   pub fn buttons(&self, action: Action) -> &[Button] {
+      self.bindings.get(&action).map(|buttons| buttons.as_slice()).unwrap_or(&[])
+   }
+
+   pub fn actions_for(&self, button: Button) -> impl Iterator<Item = Action> + '_ {
+      self.bindings.iter()
+                   .filter(move |(_, buttons)| buttons.contains(&button))
+                   .map(|(action, _)| *action)
+   }
+
+   /*
+    * Makes button the only button for action, see InputMap::bind.
+    */
+   pub fn bind(&mut self, action: Action, button: Button) {
+      for (other, buttons) in self.bindings.iter_mut() {
+         if *other != action && !other.shares_keys_with(action) {
+            buttons.retain(|bound| *bound != button);
+         }
+      }
+
+      self.bindings.insert(action, vec![button]);
+   }
+}
+
+/*
+ * Short upper case name of a button, using the common Xbox style labels
+ * for the face buttons.
+ */
+pub fn button_label(button: Button) -> String {
+   match button {
+      Button::South => String::from("A"),
+      Button::East => String::from("B"),
+      Button::West => String::from("X"),
+      Button::North => String::from("Y"),
+      Button::LeftTrigger => String::from("LB"),
+      Button::RightTrigger => String::from("RB"),
+      Button::LeftTrigger2 => String::from("LT"),
+      Button::RightTrigger2 => String::from("RT"),
+      Button::DPadUp => String::from("D-PAD  UP"),
+      Button::DPadDown => String::from("D-PAD  DOWN"),
+      Button::DPadLeft => String::from("D-PAD  LEFT"),
+      Button::DPadRight => String::from("D-PAD  RIGHT"),
+      _ => format!("{:?}", button).to_uppercase()
+   }
+}
+
+/*
+ * Which way a stick axis is pushed, taking the deadzone into account.
+ */
+pub fn axis_direction(value: f32, deadzone: f32) -> i8 {
+   if value <= -deadzone {
+      -1
+   } else if value >= deadzone {
+      1
+   } else {
+      0
+   }
+}
+
+/*
+ * How far past the deadzone a stick is pushed, rescaled to -1..1 so the
+ * player can move slowly with a light push.
+ */
+pub fn axis_strength(value: f32, deadzone: f32) -> f32 {
+   if value.abs() < deadzone {
+      return 0.0;
+   }
+
+   let strength = (value.abs() - deadzone) / (1.0 - deadzone).max(f32::EPSILON);
+   strength.min(1.0) * value.signum()
+}
+
+/*
+ * Only the left stick and the d-pad axes (some pads report the d-pad as an
+ * axis instead of buttons) are used.
+ */
+pub fn is_movement_axis(axis: Axis) -> bool {
+   matches!(axis, Axis::LeftStickX | Axis::DPadX)
+}
+
+pub fn is_menu_axis(axis: Axis) -> bool {
+   matches!(axis, Axis::LeftStickY | Axis::DPadY)
+}
+
+/*
+ * Hands out small numbers for ggez's gamepad ids, so the rest of the input
+ * code doesn't depend on ggez, and notices pads being plugged in or out.
+ * Generic over the id only so tests can plug pads in without gilrs.
+ */
+pub struct GamepadSlots<Id = GamepadId> {
+   slots: HashMap<Id, usize>,
+   names: HashMap<usize, String>,
+   next_slot: usize
+}
+
+impl<Id> Default for GamepadSlots<Id> {
+   fn default() -> Self {
+      GamepadSlots {
+         slots: HashMap::new(),
+         names: HashMap::new(),
+         next_slot: 0
+      }
+   }
+}
+
+impl<Id: Copy + Eq + Hash> GamepadSlots<Id> {
+   /*
+    * The slot of a pad, registering it if it hasn't been seen yet. The
+    * connect event for a new pad is pushed into events. A pad that is
+    * plugged back in gets a new slot.
+    */
+   pub fn slot(&mut self, id: Id, events: &mut Vec<InputEvent>) -> usize {
+      if let Some(slot) = self.slots.get(&id) {
+         return *slot;
+      }
+
+      let slot = self.next_slot;
+      self.next_slot += 1;
+      self.slots.insert(id, slot);
+      events.push(InputEvent::PadConnected(slot));
+
+      slot
+   }
+
+   pub fn name(&self, slot: usize) -> Option<&str> {
+      self.names.get(&slot).map(|name| name.as_str())
+   }
+
+   /*
+    * Compares the connected pads, with their names, against the known ones
+    * and returns connect and disconnect events for the difference.
+    */
+   pub fn update(&mut self, connected: impl IntoIterator<Item = (Id, String)>) -> Vec<InputEvent> {
+      let mut events = Vec::new();
+      let mut seen = Vec::new();
+
+      for (id, name) in connected {
+         let slot = self.slot(id, &mut events);
+         self.names.insert(slot, name);
+         seen.push(id);
+      }
+
+      let gone: Vec<Id> = self.slots.keys().filter(|id| !seen.contains(id)).copied().collect();
+      for id in gone {
+         if let Some(slot) = self.slots.remove(&id) {
+            events.push(InputEvent::PadDisconnected(slot));
+         }
+      }
+
+      events
+   }
+}
+
+impl GamepadSlots {
+   /*
+    * ggez doesn't forward gilrs' own connection events, so the connected
+    * pads are polled every frame, see update.
+    */
+   pub fn poll(&mut self, ctx: &Context) -> Vec<InputEvent> {
+      self.update(ctx.gamepad.gamepads().map(|(id, gamepad)| (id, String::from(gamepad.name()))))
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::input::{InputMap, InputState};
+
+   fn pads(ids: &[u32]) -> Vec<(u32, String)> {
+      ids.iter().map(|id| (*id, format!("PAD {}", id))).collect()
+   }
+
+   #[test]
+   fn pads_get_slots_in_the_order_they_connect() {
+      let mut slots = GamepadSlots::<u32>::default();
+
+      assert_eq!(slots.update(pads(&[7])), vec![InputEvent::PadConnected(0)]);
+      assert_eq!(slots.update(pads(&[7, 3])), vec![InputEvent::PadConnected(1)]);
+      assert_eq!(slots.update(pads(&[7, 3])), vec![]);
+
+      let mut events = Vec::new();
+      assert_eq!(slots.slot(3, &mut events), 1);
+      assert_eq!(slots.slot(7, &mut events), 0);
+      assert!(events.is_empty());
+
+      assert_eq!(slots.name(1), Some("PAD 3"));
+      assert_eq!(slots.name(2), None);
+   }
+
+   #[test]
+   fn input_from_an_unknown_pad_connects_it() {
+      let mut slots = GamepadSlots::<u32>::default();
+      let mut events = Vec::new();
+
+      assert_eq!(slots.slot(4, &mut events), 0);
+      assert_eq!(events, vec![InputEvent::PadConnected(0)]);
+
+      // Already known by the time it's polled
+      assert_eq!(slots.update(pads(&[4])), vec![]);
+   }
+
+   #[test]
+   fn reconnected_pads_get_a_new_slot() {
+      let mut slots = GamepadSlots::<u32>::default();
+      slots.update(pads(&[1, 2]));
+
+      assert_eq!(slots.update(pads(&[2])), vec![InputEvent::PadDisconnected(0)]);
+      assert_eq!(slots.update(pads(&[2])), vec![]);
+      assert_eq!(slots.update(pads(&[2, 1])), vec![InputEvent::PadConnected(2)]);
+
+      let mut events = Vec::new();
+      assert_eq!(slots.slot(1, &mut events), 2);
+      assert_eq!(slots.slot(2, &mut events), 1);
+   }
+
+   #[test]
+   fn disconnecting_a_pad_loses_focus_and_lets_go_of_it() {
+      let mut slots = GamepadSlots::<u32>::default();
+      let mut input = InputState::default();
+      let controls = InputMap::default();
+      let gamepad = GamepadMap::default();
+
+      for event in slots.update(pads(&[1])) {
+         assert!(!event.loses_focus());
+         input.handle(event, &controls, &gamepad);
+      }
+
+      input.handle(InputEvent::ButtonDown(0, Button::DPadLeft), &controls, &gamepad);
+      assert_eq!(input.movement(&controls, &gamepad), -1.0);
+
+      let events = slots.update(pads(&[]));
+      assert_eq!(events, vec![InputEvent::PadDisconnected(0)]);
+
+      for event in events {
+         assert!(event.loses_focus());
+         input.handle(event, &controls, &gamepad);
+      }
+
+      assert_eq!(input.movement(&controls, &gamepad), 0.0);
+      assert!(!input.has_gamepad());
+   }
+
+   #[test]
+   fn mute_shares_its_button_with_nothing() {
+      let mut buttons = ButtonBindings::default();
+
+      buttons.bind(Action::Mute, Button::South);
+      assert_eq!(buttons.buttons(Action::Fire), &[Button::West]);
+      assert_eq!(buttons.buttons(Action::Confirm), &[Button::Start]);
+
+      buttons.bind(Action::Back, Button::South);
+      assert!(buttons.buttons(Action::Mute).is_empty());
+   }
+}
@@ -0,0 +1,322 @@
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use ggez::glam::Vec2;
+use ggez::graphics::Rect;
+use serde::Serialize;
+
+use crate::config::GameConfig;
+use crate::events::GameEvent;
+use crate::init;
+use crate::raster::Grid;
+use crate::input_source::InputFrame;
+use crate::simulation::{GameOptions, Simulation, TickOutcome};
+use crate::sprites::SpriteManifest;
+use crate::state::EntityKind;
+use crate::timestep::TICKS_PER_SECOND;
+use crate::viewport::{PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT};
+
+// Size of the grayscale frame, a twentieth of the playfield each way
+pub const FRAME_WIDTH: usize = 96;
+pub const FRAME_HEIGHT: usize = 54;
+
+// Reward for losing a life, against 10 points for an invader
+const LIFE_LOST_REWARD: f32 = -100.0;
+
+// Episodes that are still going after an hour are cut off
+const MAX_EPISODE_TICKS: u64 = 60 * 60 * TICKS_PER_SECOND as u64;
+
+// Gray levels in the frame, so a model can tell things apart
+const PLAYER_SHADE: u8 = 255;
+const INVADER_SHADE: u8 = 170;
+const UFO_SHADE: u8 = 120;
+const PLAYER_BULLET_SHADE: u8 = 220;
+const ENEMY_BULLET_SHADE: u8 = 80;
+
+/*
+ * What the agent sees after a reset or a step.
+ *
+ * features has a fixed layout for a given formation size (rows * per_row
+ * invaders in the config), every position scaled to 0..1 of the playfield:
+ *
+ *    0      player x (center)
+ *    1      lives, as a fraction of the most lives there can be
+ *    2..5   player bullet: in the air (0 or 1), x, y
+ *    5..7   ufo: flying (0 or 1), x
+ *    then   one 0 or 1 per invader, whether it's alive, row by row
+ *    then   three per invader for its bullet: in the air (0 or 1), x, y
+ *
+ * frame is FRAME_WIDTH * FRAME_HEIGHT gray levels, row by row from the top,
+ * or empty if the environment doesn't render frames.
+ */
+#[derive(Clone, Debug, Serialize)]
+pub struct Observation {
+   pub features: Vec<f32>,
+   #[serde(skip_serializing_if = "Vec::is_empty")]
+   pub frame: Vec<u8>
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StepInfo {
+   pub score: u64,
+   pub wave: u32,
+   pub lives: u8,
+   pub ticks: u64,
+   // The step ended a wave
+   pub wave_cleared: bool,
+   // The episode was cut off rather than lost
+   pub truncated: bool
+}
+
+/*
+ * A gym style environment on top of the simulation, for training agents.
+ * Nothing is drawn or played, so it runs as fast as the simulation does.
+ */
+pub struct Environment {
+   sim: Simulation,
+   // Ticks each step lasts, the action is repeated for all of them
+   frame_skip: u32,
+   frames: bool,
+   ticks: u64
+}
+
+impl Environment {
+   pub fn new(image_dimensions: Vec2, config: GameConfig, sprites: SpriteManifest, options: GameOptions) -> Self {
+      Environment {
+         sim: Simulation::new(image_dimensions, config, sprites, options),
+         frame_skip: 1,
+         frames: true,
+         ticks: 0
+      }
+   }
+
+   pub fn with_frame_skip(mut self, frame_skip: u32) -> Self {
+      self.frame_skip = frame_skip.max(1);
+      self
+   }
+
+   /*
+    * Agents that only use the features can skip drawing the frames.
+    */
+   pub fn with_frames(mut self, frames: bool) -> Self {
+      self.frames = frames;
+      self
+   }
+
+   pub fn reset(&mut self, seed: u64) -> Observation {
+      self.sim.reset(seed);
+      self.ticks = 0;
+      self.observation()
+   }
+
+   /*
+    * Plays action for one step. The reward is the points scored, minus
+    * something for every life lost.
+    */
+   pub fn step(&mut self, action: InputFrame) -> (Observation, f32, bool, StepInfo) {
+      let dt = 1.0 / TICKS_PER_SECOND as f32;
+      let mut reward = 0.0;
+      let mut done = false;
+      let mut info = StepInfo::default();
+
+      for _ in 0..self.frame_skip {
+         let score = self.sim.score;
+         let outcome = self.sim.tick(action, dt);
+         self.ticks += 1;
+
+         reward += (self.sim.score - score) as f32;
+         reward += self.sim.drain_events()
+                           .iter()
+                           .filter(|event| **event == GameEvent::PlayerHit)
+                           .count() as f32 * LIFE_LOST_REWARD;
+
+         info.wave_cleared |= outcome == TickOutcome::WaveCleared;
+
+         if !self.sim.state.is_player_alive() {
+            done = true;
+            break;
+         }
+
+         if self.ticks >= MAX_EPISODE_TICKS {
+            done = true;
+            info.truncated = true;
+            break;
+         }
+      }
+
+      info.score = self.sim.score;
+      info.wave = self.sim.wave;
+      info.lives = self.sim.state.get_player_lives();
+      info.ticks = self.ticks;
+
+      (self.observation(), reward, done, info)
+   }
+
+   pub fn observation(&self) -> Observation {
+      Observation {
+         features: self.features(),
+         frame: if self.frames { self.frame() } else { Vec::new() }
+      }
+   }
+
+   pub fn features(&self) -> Vec<f32> {
+      let image_dimensions = self.sim.image_dimensions();
+      let state = &self.sim.state;
+      let player = state.player_bounds(image_dimensions);
+
+      let mut features = vec![
+         (player.x + player.w / 2.0) / PLAYFIELD_WIDTH,
+         state.get_player_lives() as f32 / state.config().player.max_lives.max(1) as f32
+      ];
+
+      push_position(&mut features, state.player_bullet_bounds(image_dimensions));
+
+      let ufo = state.ufo_bounds(image_dimensions);
+      features.push(ufo.is_some() as u8 as f32);
+      features.push(ufo.map_or(0.0, |ufo| ufo.x / PLAYFIELD_WIDTH));
+
+      features.extend(state.enemy_alive().iter().map(|alive| *alive as u8 as f32));
+
+      for bullet in state.enemy_bullet_slots(image_dimensions) {
+         push_position(&mut features, bullet);
+      }
+
+      features
+   }
+
+   /*
+    * The playfield in gray, every entity a filled box the size of its
+    * hitbox.
+    */
+   pub fn frame(&self) -> Vec<u8> {
+      let mut frame = Grid::new(FRAME_WIDTH, FRAME_HEIGHT, 0);
+
+      for (kind, bounds) in self.sim.state.hitboxes(self.sim.image_dimensions()) {
+         frame.fill(bounds, shade(kind));
+      }
+
+      frame.cells
+   }
+}
+
+fn shade(kind: EntityKind) -> u8 {
+   match kind {
+      EntityKind::Player => PLAYER_SHADE,
+      EntityKind::Invader => INVADER_SHADE,
+      EntityKind::Ufo => UFO_SHADE,
+      EntityKind::PlayerBullet => PLAYER_BULLET_SHADE,
+      EntityKind::EnemyBullet => ENEMY_BULLET_SHADE
+   }
+}
+
+fn push_position(features: &mut Vec<f32>, bounds: Option<Rect>) {
+   match bounds {
+      Some(bounds) => features.extend([1.0, bounds.x / PLAYFIELD_WIDTH, bounds.y / PLAYFIELD_HEIGHT]),
+      None => features.extend([0.0, 0.0, 0.0])
+   }
+}
+
+/*
+ * Serves an environment on stdin and stdout until stdin is closed.
+ */
+pub fn run(resource_dir: &Path, config: GameConfig, sprites: SpriteManifest, options: GameOptions, frame_skip: u32, frames: bool) -> Result<(), String> {
+   let sprite_sheet = resource_dir.join("space_invaders.png");
+   let image_dimensions = init::png_dimensions(&sprite_sheet)
+                              .map_err(|err| format!("Failed to read {}: {}", sprite_sheet.display(), err))?;
+
+   let mut env = Environment::new(image_dimensions, config, sprites, options).with_frame_skip(frame_skip)
+                                                                              .with_frames(frames);
+
+   serve(&mut env, io::stdin().lock(), io::stdout().lock())
+}
+
+#[derive(Serialize)]
+struct StepReply {
+   observation: Observation,
+   reward: f32,
+   done: bool,
+   info: StepInfo
+}
+
+/*
+ * Runs the environment for another process over a line protocol, e.g. a
+ * Python wrapper talking to stdin and stdout. Each command gets one line of
+ * JSON back:
+ *
+ *    reset <seed>              -> an observation
+ *    step <movement> <fire>    -> {"observation", "reward", "done", "info"}
+ *
+ * The action is an input frame like in replay files, e.g. "step -1 1".
+ */
+pub fn serve(env: &mut Environment, input: impl BufRead, mut output: impl Write) -> Result<(), String> {
+   for line in input.lines() {
+      let line = line.map_err(|err| format!("Failed to read command: {}", err))?;
+      let (command, arguments) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+
+      let reply = match command {
+         "reset" => match arguments.trim().parse::<u64>() {
+            Ok(seed) => serde_json::to_string(&env.reset(seed)),
+            Err(_) => Ok(error(&format!("invalid seed {}", arguments)))
+         },
+         "step" => match InputFrame::from_line(arguments) {
+            Some(action) => {
+               let (observation, reward, done, info) = env.step(action);
+               serde_json::to_string(&StepReply { observation, reward, done, info })
+            },
+            None => Ok(error(&format!("invalid action {}", arguments)))
+         },
+         "" => continue,
+         _ => Ok(error(&format!("unknown command {}", command)))
+      };
+
+      let reply = reply.map_err(|err| format!("Failed to write observation: {}", err))?;
+      writeln!(output, "{}", reply).and_then(|_| output.flush())
+                                   .map_err(|err| format!("Failed to write reply: {}", err))?;
+   }
+
+   Ok(())
+}
+
+fn error(message: &str) -> String {
+   serde_json::json!({ "error": message }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+   use std::path::PathBuf;
+
+   use super::*;
+
+   fn environment(start_wave: u32) -> Environment {
+      let sprite_sheet = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src").join("resources").join("space_invaders.png");
+      let options = GameOptions { start_wave, ..GameOptions::default() };
+
+      Environment::new(init::png_dimensions(&sprite_sheet).unwrap(), GameConfig::default(), SpriteManifest::default(), options)
+   }
+
+   const STEPS: usize = 1200;
+
+   #[test]
+   fn environments_in_one_process_dont_affect_each_other() {
+      let action = InputFrame::new(1.0, true);
+
+      let mut alone = environment(1);
+      alone.reset(7);
+      let expected: Vec<Vec<f32>> = (0..STEPS).map(|_| alone.step(action).0.features).collect();
+
+      // A faster formation marching next to it, reset halfway through
+      let mut env = environment(1);
+      let mut other = environment(9);
+      env.reset(7);
+      other.reset(3);
+
+      for (step, expected) in expected.iter().enumerate() {
+         other.step(InputFrame::new(-1.0, false));
+         if step == STEPS / 2 {
+            other.reset(4);
+         }
+
+         assert_eq!(&env.step(action).0.features, expected, "step {}", step);
+      }
+   }
+}
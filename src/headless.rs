@@ -0,0 +1,60 @@
+use std::path::Path;
+
+use crate::bot::Autopilot;
+use crate::config::GameConfig;
+use crate::events::{self, EventLog};
+use crate::init;
+use crate::input_source::{InputFrame, InputSource};
+use crate::simulation::{GameOptions, Simulation, TickOutcome};
+use crate::sprites::SpriteManifest;
+use crate::timestep::TICKS_PER_SECOND;
+
+// Stops a game nobody is going to lose, e.g. without input, after an hour
+pub const MAX_TICKS: u64 = 60 * 60 * TICKS_PER_SECOND as u64;
+
+/*
+ * Who plays a headless game.
+ */
+pub enum Pilot {
+   // Input comes from the source, the player stands still once it runs out
+   Input(Box<dyn InputSource>),
+   Bot(Autopilot)
+}
+
+/*
+ * Plays one game without a window or sound, as fast as possible, and prints
+ * how it went. Gives up after max_ticks. The game's events are written to
+ * event_log if given.
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn run(resource_dir: &Path, config: GameConfig, sprites: SpriteManifest, options: GameOptions, seed: u64, mut pilot: Pilot, max_ticks: u64, event_log: Option<EventLog>) -> Result<(), String> {
+   let sprite_sheet = resource_dir.join("space_invaders.png");
+   let image_dimensions = init::png_dimensions(&sprite_sheet)
+                              .map_err(|err| format!("Failed to read {}: {}", sprite_sheet.display(), err))?;
+
+   let mut sim = Simulation::new(image_dimensions, config, sprites, options);
+   sim.reset(seed);
+
+   let dt = 1.0 / TICKS_PER_SECOND as f32;
+   let mut ticks = 0;
+   let mut event_log = event_log;
+
+   while ticks < max_ticks {
+      let frame = match &mut pilot {
+         Pilot::Input(source) => source.next_frame(InputFrame::default()).unwrap_or_default(),
+         Pilot::Bot(bot) => bot.frame(&sim, dt)
+      };
+
+      if sim.tick(frame, dt) == TickOutcome::GameOver {
+         break;
+      }
+
+      events::publish(sim.count, &sim.drain_events(), &mut [&mut event_log]);
+      ticks += 1;
+   }
+
+   let outcome = if sim.state.is_player_alive() { "stopped" } else { "game over" };
+   println!("{} after {} ticks: seed {}, wave {}, score {}", outcome, ticks, seed, sim.wave, sim.score);
+
+   Ok(())
+}
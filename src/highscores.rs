@@ -0,0 +1,217 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+#[cfg(windows)]
+use std::os::windows::prelude::*;
+#[cfg(windows)]
+use winapi::um::winnt::FILE_ATTRIBUTE_HIDDEN;
+
+use crate::difficulty::Difficulty;
+
+pub const MAX_ENTRIES: usize = 10;
+pub const NAME_LENGTH: usize = 3;
+
+#[derive(Clone, Debug)]
+pub struct HighScoreEntry {
+   pub name: String,
+   pub score: u64
+}
+
+/*
+ * The best scores so far, highest first, ranked separately for every
+ * difficulty. Stored in the score file one entry per line as
+ * "NAME SCORE DIFFICULTY". Entries without a difficulty are from before
+ * there were any and count as normal, older score files that only contain
+ * a single number are read as one nameless entry.
+ */
+pub struct HighScoreTable {
+   tables: BTreeMap<Difficulty, Vec<HighScoreEntry>>,
+   path: PathBuf
+}
+
+impl HighScoreTable {
+   pub fn load(score_file: &str) -> Self {
+      let path = PathBuf::from(score_file);
+      let mut tables = BTreeMap::new();
+
+      if path.exists() {
+         match fs::read_to_string(&path) {
+            Err(err) => println!("Failed to read file {}: {}", score_file, err),
+            Ok(contents) => tables = parse(&contents)
+         }
+      } else {
+         create_score_file(&path);
+      }
+
+      for entries in tables.values_mut() {
+         entries.sort_by_key(|entry| Reverse(entry.score));
+         entries.truncate(MAX_ENTRIES);
+      }
+
+      HighScoreTable {
+         tables,
+         path
+      }
+   }
+
+   pub fn entries(&self, difficulty: Difficulty) -> &[HighScoreEntry] {
+      self.tables.get(&difficulty).map(|entries| entries.as_slice()).unwrap_or(&[])
+   }
+
+   pub fn best(&self, difficulty: Difficulty) -> u64 {
+      self.entries(difficulty).first().map(|entry| entry.score).unwrap_or(0)
+   }
+
+   /*
+    * Whether the score is good enough to make it onto the difficulty's table.
+    */
+   pub fn qualifies(&self, score: u64, difficulty: Difficulty) -> bool {
+      let entries = self.entries(difficulty);
+      score > 0 && (entries.len() < MAX_ENTRIES || score > entries[entries.len() - 1].score)
+   }
+
+   /*
+    * Adds an entry and saves the table. Returns the place it got on its
+    * difficulty's table, if any.
+    */
+   pub fn insert(&mut self, name: &str, score: u64, difficulty: Difficulty) -> Option<usize> {
+      if !self.qualifies(score, difficulty) {
+         return None;
+      }
+
+      let entries = self.tables.entry(difficulty).or_default();
+      let position = entries.iter().position(|entry| score > entry.score).unwrap_or(entries.len());
+      entries.insert(position, HighScoreEntry { name: String::from(name), score });
+      entries.truncate(MAX_ENTRIES);
+      self.save();
+
+      Some(position)
+   }
+
+   pub fn save(&self) {
+      let contents: String = self.tables.iter()
+                                        .flat_map(|(difficulty, entries)| entries.iter().map(move |entry| (difficulty, entry)))
+                                        .map(|(difficulty, entry)| format!("{} {} {}\n", entry.name, entry.score, difficulty.name()))
+                                        .collect();
+
+      if !self.path.exists() {
+         create_score_file(&self.path);
+      }
+
+      // The file is hidden on windows, where opening it with truncate fails,
+      // so open it as is and truncate it afterwards.
+      let file = OpenOptions::new()
+                 .write(true)
+                 .open(&self.path);
+
+      match file {
+         Err(err) => eprintln!("Failed to open high score file: {}", err),
+         Ok(mut file) => {
+            if let Err(err) = file.set_len(0).and_then(|_| file.write_all(contents.as_bytes())) {
+               eprintln!("Failed to write high score: {}", err);
+            }
+         }
+      }
+   }
+}
+
+fn parse(contents: &str) -> BTreeMap<Difficulty, Vec<HighScoreEntry>> {
+   let mut tables: BTreeMap<Difficulty, Vec<HighScoreEntry>> = BTreeMap::new();
+
+   for line in contents.lines() {
+      let line = line.trim();
+      if line.is_empty() {
+         continue;
+      }
+
+      let (rest, difficulty) = match line.rsplit_once(' ').and_then(|(rest, name)| Some((rest, Difficulty::from_name(name)?))) {
+         Some((rest, difficulty)) => (rest.trim(), difficulty),
+         None => (line, Difficulty::Normal)
+      };
+
+      let (name, score) = match rest.rsplit_once(' ') {
+         Some((name, score)) => (name.trim(), score),
+         None => ("???", rest)
+      };
+
+      match score.parse::<u64>() {
+         Err(err) => println!("Failed to parse high score? {}", err),
+         Ok(score) => tables.entry(difficulty).or_default().push(HighScoreEntry { name: String::from(name), score })
+      }
+   }
+
+   tables
+}
+
+fn create_score_file(path: &Path) {
+   let mut options = OpenOptions::new();
+   options.write(true).create(true);
+
+   // For windows we must set the hidden attribute ourselves
+   #[cfg(windows)]
+   options.attributes(FILE_ATTRIBUTE_HIDDEN);
+
+   if let Err(err) = options.open(path) {
+      println!("Failed to create high score file: {}", err);
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn scores(entries: &[HighScoreEntry]) -> Vec<(&str, u64)> {
+      entries.iter().map(|entry| (entry.name.as_str(), entry.score)).collect()
+   }
+
+   #[test]
+   fn legacy_score_files_are_one_nameless_normal_entry() {
+      let tables = parse("1250\n");
+
+      assert_eq!(tables.len(), 1);
+      assert_eq!(scores(&tables[&Difficulty::Normal]), [("???", 1250)]);
+   }
+
+   #[test]
+   fn entries_without_a_difficulty_count_as_normal() {
+      let tables = parse("ABC 300\nXYZ 200 hard\n\nJO E 100 easy\nBAD score\n");
+
+      assert_eq!(scores(&tables[&Difficulty::Normal]), [("ABC", 300)]);
+      assert_eq!(scores(&tables[&Difficulty::Hard]), [("XYZ", 200)]);
+      assert_eq!(scores(&tables[&Difficulty::Easy]), [("JO E", 100)]);
+   }
+
+   #[test]
+   fn every_difficulty_is_ranked_and_truncated_on_its_own() {
+      let path = std::env::temp_dir().join(format!("space_invaders_scores_{}", std::process::id()));
+      let mut contents = String::from("TOP 5000 hard\n");
+      for score in 1..=12 {
+         contents += &format!("N{:02} {}\n", score, score * 10);
+      }
+      fs::write(&path, contents).unwrap();
+
+      let mut table = HighScoreTable::load(path.to_str().unwrap());
+      assert_eq!(table.entries(Difficulty::Normal).len(), MAX_ENTRIES);
+      assert_eq!(table.best(Difficulty::Normal), 120);
+      assert_eq!(table.entries(Difficulty::Normal)[MAX_ENTRIES - 1].score, 30);
+      assert_eq!(table.best(Difficulty::Hard), 5000);
+      assert!(table.entries(Difficulty::Easy).is_empty());
+
+      // Too low for normal, but a first place on easy
+      assert!(!table.qualifies(30, Difficulty::Normal));
+      assert_eq!(table.insert("NEW", 30, Difficulty::Easy), Some(0));
+      assert_eq!(table.insert("MID", 75, Difficulty::Normal), Some(5));
+      assert_eq!(table.entries(Difficulty::Normal)[MAX_ENTRIES - 1].score, 40);
+
+      // What was saved reads back the same
+      let reloaded = HighScoreTable::load(path.to_str().unwrap());
+      fs::remove_file(&path).unwrap();
+
+      for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+         assert_eq!(scores(reloaded.entries(difficulty)), scores(table.entries(difficulty)));
+      }
+   }
+}
@@ -0,0 +1,60 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// How often the files are checked, in seconds.
+const POLL_INTERVAL: f32 = 0.5;
+
+struct WatchedFile {
+   path: PathBuf,
+   modified: Option<SystemTime>
+}
+
+/*
+ * Notices when files are changed on disk by checking their modification
+ * times every now and then. A file that doesn't exist yet is reported once
+ * it's created.
+ */
+#[derive(Default)]
+pub struct FileWatcher {
+   files: Vec<WatchedFile>,
+   elapsed: f32
+}
+
+impl FileWatcher {
+   pub fn watch(&mut self, path: &Path) {
+      self.files.push(WatchedFile { path: PathBuf::from(path), modified: modified(path) });
+   }
+
+   /*
+    * The files that changed since the last check. dt is the time since the
+    * last call, in seconds.
+    */
+   pub fn poll(&mut self, dt: f32) -> Vec<PathBuf> {
+      self.elapsed += dt;
+
+      if self.elapsed < POLL_INTERVAL {
+         return Vec::new();
+      }
+
+      self.elapsed = 0.0;
+      let mut changed = Vec::new();
+
+      for file in self.files.iter_mut() {
+         let modified = modified(&file.path);
+
+         // A file that was removed stays as it was until it comes back
+         if modified.is_some() && modified != file.modified {
+            changed.push(file.path.clone());
+         }
+
+         file.modified = modified;
+      }
+
+      changed
+   }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+   fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -1,104 +1,119 @@
-use ggez::{Context, graphics::{self, Image, DrawParam, Rect}, glam::Vec2};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::OnceLock;
 
-use crate::entities::{ Enemy, Sprite, Player };
-use crate::state::NUM_ROWS;
+use ab_glyph::{Font, FontVec};
+use ggez::{Context, graphics::{self, Rect}, glam::Vec2};
+
+use crate::entities::{ Enemy, Player, Ufo };
+use crate::sprites::{self, SpriteManifest};
+
+pub const GAME_FONT: &str = "MainFont";
+// Built into ggez, draws whatever the game font has no glyphs for
+pub const FALLBACK_FONT: &str = "LiberationMono-Regular";
+
+// The game font as loaded by init_font, to look up which characters it has
+static GAME_FONT_GLYPHS: OnceLock<FontVec> = OnceLock::new();
 
 pub fn init_font(ctx: &mut Context, path: &str) {
-   let font_data = graphics::FontData::from_path(ctx, path).unwrap();
-   ctx.gfx.add_font("MainFont", font_data);
+   let mut data = Vec::new();
+   ctx.fs.open(path).unwrap().read_to_end(&mut data).unwrap();
+
+   let _ = GAME_FONT_GLYPHS.set(FontVec::try_from_vec(data.clone()).unwrap());
+   ctx.gfx.add_font(GAME_FONT, graphics::FontData::from_vec(data).unwrap());
 }
 
-//Player: Rect::new(0.25, 0.9, 0.16, 0.1)
-//Player death frame: Rect::new(0.40, 0.9, 0.19, 0.12)
-pub fn init_player(image: &Image, scale: Vec2, screen_width: f32, screen_height: f32) -> Box<Player> {
-   let image_dimensions = Vec2::new(image.height() as f32, image.width() as f32);
-   let mut player_instance_array = Vec::new();
-   player_instance_array.push(DrawParam::default().src(Rect::new(0.25, 0.9, 0.16, 0.1)));
+/*
+ * Whether the game font can draw c. It only has letters, digits, spaces and
+ * exclamation marks, anything else (punctuation, a gamepad's name) is drawn
+ * in the fallback font. Before init_font everything counts as covered.
+ */
+pub fn has_glyph(c: char) -> bool {
+   GAME_FONT_GLYPHS.get().is_none_or(|font| font.glyph_id(c).0 != 0)
+}
 
-   let mut player_death_instance_array = Vec::new();
-   player_death_instance_array.push(DrawParam::default().src(Rect::new(0.40, 0.9, 0.19, 0.12)));
+/*
+ * Size of a PNG as (height, width), the way the entities expect image
+ * dimensions, read from its header. Lets the game run without a window,
+ * where the sprite sheet is never loaded as a texture.
+ */
+pub fn png_dimensions(path: &Path) -> io::Result<Vec2> {
+   let mut header = [0u8; 24];
+   File::open(path)?.read_exact(&mut header)?;
+
+   if &header[1..4] != b"PNG" || &header[12..16] != b"IHDR" {
+      return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a PNG", path.display())));
+   }
+
+   let width = u32::from_be_bytes([header[16], header[17], header[18], header[19]]);
+   let height = u32::from_be_bytes([header[20], header[21], header[22], header[23]]);
+
+   Ok(Vec2::new(height as f32, width as f32))
+}
 
-   let mut bullet_instance_array = Vec::new();
-   bullet_instance_array.push(DrawParam::default().src(Rect::new(0.83, 0.55, 0.15, 0.12)));
+// Positions are in logical playfield units (see viewport.rs), not window pixels.
 
-   let sprite_alive = Sprite::new(player_instance_array);
+// Where the sprites are on the sheet comes from the sprite manifest, see sprites.rs.
+pub fn init_player(sprites: &SpriteManifest, image_dimensions: Vec2, scale: Vec2, playfield_width: f32, playfield_height: f32) -> Box<Player> {
+   let sprite_alive = sprites::sprite(&sprites.player);
 
    let frame_dimension = sprite_alive.get_frame_dimensions(0).unwrap();
 
    Box::new(
       Player::new(
          sprite_alive,
-         Sprite::new(player_death_instance_array),
-         Sprite::new(bullet_instance_array),
+         sprites::sprite(&sprites.player_death),
+         sprites::sprite(&sprites.bullet),
          Rect {
-            x: screen_width / 2.2 - (frame_dimension.y * scale.x * image_dimensions.y / 2.0),
-            y: screen_height - (frame_dimension.x * scale.y * image_dimensions.x + 10.0),
+            x: playfield_width / 2.2 - (frame_dimension.y * scale.x * image_dimensions.y / 2.0),
+            y: playfield_height - (frame_dimension.x * scale.y * image_dimensions.x + 10.0),
             w: scale.x,
             h: scale.y }
       )
    )
 }
 
-pub fn init_enemies(image: &Image, num_enemies_on_row: u32, scale: Vec2, screen_width: f32, screen_height: f32) -> Vec<Enemy> {
-   //Enemy 1:  Rect::new(0.0, 0.0, 0.25, 0.12), Rect::new(0.25, 0.0, 0.25, 0.12)
-   //Enemy 2:  Rect::new(0.55, 0.0, 0.20, 0.12), Rect::new(0.76, 0.0, 0.20, 0.12)
-   //Enemy 3:  Rect::new(0.0, 0.175, 0.25, 0.12), Rect::new(0.25, 0.175, 0.25, 0.12)
-   //Enemy death frame: Rect::new(0.62, 0.9, 0.20, 0.12)
-   //Bullet: Rect::new(0.83, 0.55, 0.15, 0.12)
+pub fn init_ufo(sprites: &SpriteManifest, scale: Vec2, playfield_height: f32) -> Ufo {
+   // Starts off screen, State::update decides when it flies
+   Ufo::new(sprites::sprite(&sprites.ufo),
+            Rect {
+               x: 0.0,
+               y: playfield_height / 16.0,
+               w: scale.x,
+               h: scale.y })
+}
 
+pub fn init_enemies(sprites: &SpriteManifest, image_dimensions: Vec2, num_rows: u8, num_enemies_on_row: u8, scale: Vec2, playfield_width: f32, playfield_height: f32) -> Vec<Enemy> {
    let mut enemies: Vec<Enemy> = Vec::new();
-   let image_dimensions = Vec2::new(image.height() as f32, image.width() as f32);
-
-   let mut enemy1_instance_array = Vec::new();
-   enemy1_instance_array.push(DrawParam::default().src(Rect::new(0.0, 0.0, 0.25, 0.12)));
-   enemy1_instance_array.push(DrawParam::default().src(Rect::new(0.25, 0.0, 0.25, 0.12)));
-
-   let enemy1_sprite_alive = Sprite::new(enemy1_instance_array);
-
-   // let mut enemy2_instance_array = Vec::new();
-   // enemy2_instance_array.push(DrawParam::default().src(Rect::new(0.55, 0.0, 0.20, 0.12)));
-   // enemy2_instance_array.push(DrawParam::default().src(Rect::new(0.76, 0.0, 0.20, 0.12)));
-
-   // let enemy2_sprite_alive = Sprite::new(enemy2_instance_array);
-
-   let mut enemy3_instance_array = Vec::new();
-   enemy3_instance_array.push(DrawParam::default().src(Rect::new(0.0, 0.175, 0.25, 0.12)));
-   enemy3_instance_array.push(DrawParam::default().src(Rect::new(0.25, 0.175, 0.25, 0.12)));
-
-   let enemy3_sprite_alive = Sprite::new(enemy3_instance_array);
-
-   let mut enemy_death_instance_array = Vec::new();
-   enemy_death_instance_array.push(DrawParam::default().src(Rect::new(0.62, 0.9, 0.20, 0.12)));
-
-   let enemy_death_sprite = Sprite::new(enemy_death_instance_array);
 
-   let mut bullet_instance_array= Vec::new();
-   bullet_instance_array.push(DrawParam::default().src(Rect::new(0.83, 0.55, 0.15, 0.12)));
+   let enemy1_sprite_alive = sprites::sprite(&sprites.invader_a);
+   let enemy3_sprite_alive = sprites::sprite(&sprites.invader_b);
+   let enemy_death_sprite = sprites::sprite(&sprites.invader_death);
+   let bullet_sprite = sprites::sprite(&sprites.bullet);
 
-   let bullet_sprite = Sprite::new(bullet_instance_array);
    let mut count = 0;
 
    let enemy1_dim = enemy1_sprite_alive.clone().get_frame_dimensions(0).unwrap();
-   // let enemy2_dim = enemy2_sprite_alive.clone().get_frame_dimensions(0).unwrap();
    let enemy3_dim = enemy3_sprite_alive.clone().get_frame_dimensions(0).unwrap();
 
    // Figure out a way here to make the enemies init more left, the bigger they are :) For now it's like this
-   for row in 0..NUM_ROWS {
+   for row in 0..num_rows {
       for enemy_count in 0..num_enemies_on_row {
          if count == 0 {
             enemies.push(Enemy::new(enemy1_sprite_alive.clone(),
                                     enemy_death_sprite.clone(),
                                     bullet_sprite.clone(),
-                                    Rect {x: (screen_width / 3.0) + enemy_count as f32 * enemy1_dim.y * image_dimensions.y * scale.x,
-                                                    y: screen_height / 12.0 + row as f32 * enemy1_dim.x * image_dimensions.x + ((row as f32 * (enemy1_dim.x * image_dimensions.x * scale.y)) / 2.0),
+                                    Rect {x: (playfield_width / 3.0) + enemy_count as f32 * enemy1_dim.y * image_dimensions.y * scale.x,
+                                                    y: playfield_height / 12.0 + row as f32 * enemy1_dim.x * image_dimensions.x + ((row as f32 * (enemy1_dim.x * image_dimensions.x * scale.y)) / 2.0),
                                                     w: scale.x,
                                                     h: scale.y }));
          } else {
             enemies.push(Enemy::new(enemy3_sprite_alive.clone(),
                                     enemy_death_sprite.clone(),
                                     bullet_sprite.clone(),
-                                    Rect {x: (screen_width / 3.0) + enemy_count as f32 * enemy3_dim.y * image_dimensions.y * scale.x,
-                                                    y: screen_height / 12.0 + row as f32 * enemy3_dim.x * image_dimensions.x  + ((row as f32 * (enemy3_dim.x * image_dimensions.x * scale.y)) / 2.0),
+                                    Rect {x: (playfield_width / 3.0) + enemy_count as f32 * enemy3_dim.y * image_dimensions.y * scale.x,
+                                                    y: playfield_height / 12.0 + row as f32 * enemy3_dim.x * image_dimensions.x  + ((row as f32 * (enemy3_dim.x * image_dimensions.x * scale.y)) / 2.0),
                                                     w: scale.x,
                                                     h: scale.y }));
          }
@@ -1,26 +1,459 @@
-use ggez::Context;
+use std::collections::{BTreeMap, HashSet};
+
+use ggez::event::{Axis, Button};
 use ggez::input::keyboard::{KeyCode};
+use serde::{Deserialize, Serialize};
+use serde::de::IntoDeserializer;
 
-pub fn update_shoot(ctx: &Context) -> bool {
-   let k_ctx = &ctx.keyboard;
+use crate::gamepad::{self, GamepadMap};
+use crate::input_source::InputFrame;
 
-   k_ctx.is_key_just_pressed(KeyCode::Space)
+/*
+ * Everything the player can do. The game only ever asks about actions, the
+ * InputMap and GamepadMap decide which keys and buttons trigger them.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+   MoveLeft,
+   MoveRight,
+   Fire,
+   Pause,
+   Confirm,
+   Back,
+   MenuUp,
+   MenuDown,
+   // Works on every screen
+   Mute
 }
 
-pub fn is_space_pressed(ctx: &Context) -> bool {
-   let k_ctx = &ctx.keyboard;
+impl Action {
+   pub const ALL: [Action; 9] = [
+      Action::MoveLeft,
+      Action::MoveRight,
+      Action::Fire,
+      Action::Pause,
+      Action::Confirm,
+      Action::Back,
+      Action::MenuUp,
+      Action::MenuDown,
+      Action::Mute
+   ];
+
+   /*
+    * Name of the action in the settings file.
+    */
+   pub fn name(&self) -> &'static str {
+      match self {
+         Action::MoveLeft => "move_left",
+         Action::MoveRight => "move_right",
+         Action::Fire => "fire",
+         Action::Pause => "pause",
+         Action::Confirm => "confirm",
+         Action::Back => "back",
+         Action::MenuUp => "menu_up",
+         Action::MenuDown => "menu_down",
+         Action::Mute => "mute"
+      }
+   }
+
+   pub fn from_name(name: &str) -> Option<Action> {
+      Action::ALL.iter().copied().find(|action| action.name() == name)
+   }
+
+   pub fn label(&self) -> &'static str {
+      match self {
+         Action::MoveLeft => "MOVE  LEFT",
+         Action::MoveRight => "MOVE  RIGHT",
+         Action::Fire => "FIRE",
+         Action::Pause => "PAUSE",
+         Action::Confirm => "CONFIRM",
+         Action::Back => "BACK",
+         Action::MenuUp => "MENU  UP",
+         Action::MenuDown => "MENU  DOWN",
+         Action::Mute => "MUTE"
+      }
+   }
 
-   k_ctx.is_key_just_pressed(KeyCode::Space)
+   /*
+    * Actions in the same group are used at the same time, so they shouldn't
+    * share a key. Actions in different groups (e.g. Fire and Confirm) may.
+    * Mute works on every screen, so it can't share a key with anything.
+    */
+   pub fn shares_keys_with(&self, other: Action) -> bool {
+      *self != Action::Mute && other != Action::Mute && self.is_gameplay() != other.is_gameplay()
+   }
+
+   fn is_gameplay(&self) -> bool {
+      matches!(self, Action::MoveLeft | Action::MoveRight | Action::Fire | Action::Pause)
+   }
+
+   fn index(&self) -> usize {
+      *self as usize
+   }
 }
 
-pub fn update_movement(ctx: &Context) -> i8 {
-   let k_ctx = &ctx.keyboard;
+/*
+ * Which keys trigger which action. Every action can have several keys.
+ * Stored in the user settings file as e.g. fire = ["Space"], using the
+ * names of the KeyCode variants.
+ */
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, Vec<String>>", into = "BTreeMap<String, Vec<String>>")]
+pub struct InputMap {
+   bindings: BTreeMap<Action, Vec<KeyCode>>
+}
 
-   if k_ctx.is_key_pressed(KeyCode::Left) {
-      -1
-   } else if k_ctx.is_key_pressed(KeyCode::Right) {
-      1
-   } else {
-      0
+impl Default for InputMap {
+   fn default() -> Self {
+      let mut bindings = BTreeMap::new();
+
+      bindings.insert(Action::MoveLeft, vec![KeyCode::Left, KeyCode::A]);
+      bindings.insert(Action::MoveRight, vec![KeyCode::Right, KeyCode::D]);
+      bindings.insert(Action::Fire, vec![KeyCode::Space]);
+      bindings.insert(Action::Pause, vec![KeyCode::Escape, KeyCode::P]);
+      bindings.insert(Action::Confirm, vec![KeyCode::Space, KeyCode::Return]);
+      bindings.insert(Action::Back, vec![KeyCode::Escape]);
+      bindings.insert(Action::MenuUp, vec![KeyCode::Up, KeyCode::W]);
+      bindings.insert(Action::MenuDown, vec![KeyCode::Down, KeyCode::S]);
+      bindings.insert(Action::Mute, vec![KeyCode::M]);
+
+      InputMap { bindings }
+   }
+}
+
+impl From<BTreeMap<String, Vec<String>>> for InputMap {
+   // Actions missing from the file keep their default keys, unknown actions
+   // and keys are skipped so one typo doesn't throw away the whole file.
+   fn from(bindings: BTreeMap<String, Vec<String>>) -> Self {
+      let mut map = InputMap::default();
+
+      for (name, key_names) in bindings {
+         let action = match Action::from_name(&name) {
+            Some(action) => action,
+            None => {
+               eprintln!("Unknown action in key bindings: {}", name);
+               continue;
+            }
+         };
+
+         let keys = key_names.iter().filter_map(|key_name| {
+            let key: Result<KeyCode, serde::de::value::Error> = KeyCode::deserialize(key_name.as_str().into_deserializer());
+
+            if key.is_err() {
+               eprintln!("Unknown key {} bound to {}", key_name, name);
+            }
+
+            key.ok()
+         }).collect();
+
+         map.bindings.insert(action, keys);
+      }
+
+      map
    }
-}
\ No newline at end of file
+}
+
+impl From<InputMap> for BTreeMap<String, Vec<String>> {
+   fn from(map: InputMap) -> Self {
+      map.bindings.iter()
+                  .map(|(action, keys)| (String::from(action.name()), keys.iter().map(|key| format!("{:?}", key)).collect()))
+                  .collect()
+   }
+}
+
+impl InputMap {
+   pub fn keys(&self, action: Action) -> &[KeyCode] {
+      self.bindings.get(&action).map(|keys| keys.as_slice()).unwrap_or(&[])
+   }
+
+   pub fn actions_for(&self, key: KeyCode) -> impl Iterator<Item = Action> + '_ {
+      self.bindings.iter()
+                   .filter(move |(_, keys)| keys.contains(&key))
+                   .map(|(action, _)| *action)
+   }
+
+   /*
+    * Makes key the only key for action. The key is taken away from other
+    * actions that are used at the same time.
+    */
+   pub fn bind(&mut self, action: Action, key: KeyCode) {
+      for (other, keys) in self.bindings.iter_mut() {
+         if *other != action && !other.shares_keys_with(action) {
+            keys.retain(|bound| *bound != key);
+         }
+      }
+
+      self.bindings.insert(action, vec![key]);
+   }
+
+}
+
+/*
+ * Human readable, upper case name of a key.
+ */
+pub fn key_label(key: KeyCode) -> String {
+   match key {
+      KeyCode::Space => String::from("SPACEBAR"),
+      KeyCode::Return => String::from("ENTER"),
+      KeyCode::Escape => String::from("ESC"),
+      KeyCode::Back => String::from("BACKSPACE"),
+      KeyCode::LShift | KeyCode::RShift => String::from("SHIFT"),
+      KeyCode::LControl | KeyCode::RControl => String::from("CTRL"),
+      KeyCode::LAlt | KeyCode::RAlt => String::from("ALT"),
+      KeyCode::Key0 | KeyCode::Key1 | KeyCode::Key2 | KeyCode::Key3 | KeyCode::Key4 |
+      KeyCode::Key5 | KeyCode::Key6 | KeyCode::Key7 | KeyCode::Key8 | KeyCode::Key9 => {
+         format!("{:?}", key).trim_start_matches("Key").to_string()
+      },
+      _ => format!("{:?}", key).to_uppercase()
+   }
+}
+
+/*
+ * Raw input, as handed over by the event loop. Pads are identified by the
+ * slot GamepadSlots gave them.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+   KeyDown(KeyCode),
+   KeyUp(KeyCode),
+   ButtonDown(usize, Button),
+   ButtonUp(usize, Button),
+   AxisMoved(usize, Axis, f32),
+   PadConnected(usize),
+   PadDisconnected(usize)
+}
+
+impl InputEvent {
+   /*
+    * Whether the game should react as if the window lost focus, like a
+    * controller being unplugged mid-game.
+    */
+   pub fn loses_focus(&self) -> bool {
+      matches!(self, InputEvent::PadDisconnected(_))
+   }
+}
+
+#[derive(Default)]
+struct PadState {
+   buttons: HashSet<Button>,
+   movement_axis: f32,
+   menu_axis: f32
+}
+
+/*
+ * What is held down right now, plus presses seen between simulation ticks.
+ * With a fixed timestep a frame can run zero ticks, so a "just pressed"
+ * action must be held on to until the next tick consumes it.
+ */
+#[derive(Default)]
+pub struct InputState {
+   keys: HashSet<KeyCode>,
+   pads: BTreeMap<usize, PadState>,
+   pressed: [bool; Action::ALL.len()],
+   // Raw key and button for screens that wait for any input, like rebinding
+   last_key: Option<KeyCode>,
+   last_button: Option<Button>
+}
+
+impl InputState {
+   pub fn handle(&mut self, event: InputEvent, controls: &InputMap, gamepad: &GamepadMap) {
+      match event {
+         InputEvent::KeyDown(key) => {
+            if self.keys.insert(key) {
+               for action in Action::ALL {
+                  self.pressed[action.index()] |= controls.keys(action).contains(&key);
+               }
+
+               self.last_key = Some(key);
+            }
+         },
+         InputEvent::KeyUp(key) => {
+            self.keys.remove(&key);
+         },
+         InputEvent::ButtonDown(pad, button) => {
+            if self.pads.entry(pad).or_default().buttons.insert(button) {
+               for action in gamepad.buttons.actions_for(button) {
+                  self.pressed[action.index()] = true;
+               }
+
+               self.last_button = Some(button);
+            }
+         },
+         InputEvent::ButtonUp(pad, button) => {
+            if let Some(state) = self.pads.get_mut(&pad) {
+               state.buttons.remove(&button);
+            }
+         },
+         InputEvent::AxisMoved(pad, axis, value) => {
+            let state = self.pads.entry(pad).or_default();
+
+            // Pushing a stick past the deadzone counts as a press, so sticks
+            // can be used in menus too
+            if gamepad::is_movement_axis(axis) {
+               let before = gamepad::axis_direction(state.movement_axis, gamepad.deadzone);
+               let after = gamepad::axis_direction(value, gamepad.deadzone);
+               state.movement_axis = value;
+
+               if after != before {
+                  match after {
+                     -1 => self.pressed[Action::MoveLeft.index()] = true,
+                     1 => self.pressed[Action::MoveRight.index()] = true,
+                     _ => {}
+                  }
+               }
+            } else if gamepad::is_menu_axis(axis) {
+               let before = gamepad::axis_direction(state.menu_axis, gamepad.deadzone);
+               let after = gamepad::axis_direction(value, gamepad.deadzone);
+               state.menu_axis = value;
+
+               // Up is positive on gamepads
+               if after != before {
+                  match after {
+                     -1 => self.pressed[Action::MenuDown.index()] = true,
+                     1 => self.pressed[Action::MenuUp.index()] = true,
+                     _ => {}
+                  }
+               }
+            }
+         },
+         InputEvent::PadConnected(pad) => {
+            self.pads.insert(pad, PadState::default());
+         },
+         InputEvent::PadDisconnected(pad) => {
+            self.pads.remove(&pad);
+         }
+      }
+   }
+
+   pub fn has_gamepad(&self) -> bool {
+      !self.pads.is_empty()
+   }
+
+   pub fn is_held(&self, action: Action, controls: &InputMap, gamepad: &GamepadMap) -> bool {
+      controls.keys(action).iter().any(|key| self.keys.contains(key)) ||
+      self.pads.values().any(|pad| gamepad.buttons.buttons(action).iter().any(|button| pad.buttons.contains(button)))
+   }
+
+   /*
+    * Horizontal movement from -1 (full speed left) to 1 (full speed right).
+    * Keys and the d-pad always move at full speed, the stick can go slower.
+    */
+   pub fn movement(&self, controls: &InputMap, gamepad: &GamepadMap) -> f32 {
+      if self.is_held(Action::MoveLeft, controls, gamepad) {
+         return -1.0;
+      }
+
+      if self.is_held(Action::MoveRight, controls, gamepad) {
+         return 1.0;
+      }
+
+      self.pads.values()
+               .map(|pad| gamepad::axis_strength(pad.movement_axis, gamepad.deadzone))
+               .fold(0.0, |strongest: f32, strength| if strength.abs() > strongest.abs() { strength } else { strongest })
+   }
+
+   /*
+    * The local player's input for one simulation tick. Consumes Fire.
+    */
+   pub fn frame(&mut self, controls: &InputMap, gamepad: &GamepadMap) -> InputFrame {
+      let movement = self.movement(controls, gamepad);
+      InputFrame::new(movement, self.take(Action::Fire))
+   }
+
+   /*
+    * Lets go of everything, e.g. when the window loses focus and the key up
+    * events would never arrive.
+    */
+   pub fn release_all(&mut self) {
+      self.keys.clear();
+
+      for pad in self.pads.values_mut() {
+         *pad = PadState::default();
+      }
+   }
+
+   /*
+    * Forgets every press that wasn't consumed. What is held stays held.
+    */
+   pub fn clear_presses(&mut self) {
+      self.pressed = [false; Action::ALL.len()];
+      self.last_key = None;
+      self.last_button = None;
+   }
+
+   /*
+    * Whether anything is held or was pressed and not consumed yet.
+    */
+   pub fn is_active(&self) -> bool {
+      !self.keys.is_empty() ||
+      self.pads.values().any(|pad| !pad.buttons.is_empty()) ||
+      self.pressed.iter().any(|pressed| *pressed) ||
+      self.last_key.is_some() ||
+      self.last_button.is_some()
+   }
+
+   pub fn take(&mut self, action: Action) -> bool {
+      std::mem::take(&mut self.pressed[action.index()])
+   }
+
+   pub fn take_last_key(&mut self) -> Option<KeyCode> {
+      self.last_key.take()
+   }
+
+   pub fn take_last_button(&mut self) -> Option<Button> {
+      self.last_button.take()
+   }
+
+   /*
+    * -1 for up, 1 for down in menus.
+    */
+   pub fn take_menu_movement(&mut self) -> i8 {
+      let up = self.take(Action::MenuUp);
+      let down = self.take(Action::MenuDown);
+
+      down as i8 - up as i8
+   }
+
+   /*
+    * -1 for left, 1 for right in menus.
+    */
+   pub fn take_menu_horizontal(&mut self) -> i8 {
+      let left = self.take(Action::MoveLeft);
+      let right = self.take(Action::MoveRight);
+
+      right as i8 - left as i8
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn binding_a_key_takes_it_from_actions_used_at_the_same_time() {
+      let mut controls = InputMap::default();
+
+      controls.bind(Action::Fire, KeyCode::Left);
+      assert_eq!(controls.keys(Action::Fire), &[KeyCode::Left]);
+      assert_eq!(controls.keys(Action::MoveLeft), &[KeyCode::A]);
+
+      // Fire and Confirm are never used at the same time
+      controls.bind(Action::Fire, KeyCode::Return);
+      assert_eq!(controls.keys(Action::Confirm), &[KeyCode::Space, KeyCode::Return]);
+   }
+
+   #[test]
+   fn mute_shares_its_key_with_nothing() {
+      let mut controls = InputMap::default();
+
+      controls.bind(Action::Fire, KeyCode::M);
+      assert_eq!(controls.keys(Action::Fire), &[KeyCode::M]);
+      assert!(controls.keys(Action::Mute).is_empty());
+
+      controls.bind(Action::Mute, KeyCode::Return);
+      assert_eq!(controls.keys(Action::Confirm), &[KeyCode::Space]);
+
+      controls.bind(Action::MenuUp, KeyCode::Return);
+      assert!(controls.keys(Action::Mute).is_empty());
+   }
+}
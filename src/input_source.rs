@@ -0,0 +1,333 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+
+const REPLAY_HEADER: &str = "# space_invaders replay v1";
+
+/*
+ * What the player does during one simulation tick. This is all the
+ * simulation ever learns about input.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InputFrame {
+   // -1 is full speed left, 1 full speed right
+   pub movement: f32,
+   pub fire: bool
+}
+
+impl InputFrame {
+   pub fn new(movement: f32, fire: bool) -> Self {
+      InputFrame { movement: movement.clamp(-1.0, 1.0), fire }
+   }
+
+   /*
+    * One line of a replay file or the network protocol: "<movement> <fire>",
+    * e.g. "-1 0" or "0.35 1".
+    */
+   pub fn to_line(self) -> String {
+      format!("{} {}", self.movement, self.fire as u8)
+   }
+
+   pub fn from_line(line: &str) -> Option<InputFrame> {
+      let mut parts = line.split_whitespace();
+      let movement = parts.next()?.parse::<f32>().ok()?;
+      let fire = match parts.next()? {
+         "0" => false,
+         "1" => true,
+         _ => return None
+      };
+
+      if parts.next().is_some() || !movement.is_finite() {
+         return None;
+      }
+
+      Some(InputFrame::new(movement, fire))
+   }
+}
+
+/*
+ * Produces one InputFrame per simulation tick. live is what the local player
+ * is doing this tick, sources that are driven by something else ignore it.
+ * Returns None once the source has run out, e.g. at the end of a replay.
+ */
+pub trait InputSource {
+   fn next_frame(&mut self, live: InputFrame) -> Option<InputFrame>;
+}
+
+/*
+ * The local player, through the keyboard or a gamepad.
+ */
+pub struct LiveSource;
+
+impl InputSource for LiveSource {
+   fn next_frame(&mut self, live: InputFrame) -> Option<InputFrame> {
+      Some(live)
+   }
+}
+
+/*
+ * A fixed sequence of frames, e.g. the ones in a replay file.
+ */
+pub struct ScriptedSource {
+   frames: VecDeque<InputFrame>
+}
+
+impl ScriptedSource {
+   pub fn new(frames: Vec<InputFrame>) -> Self {
+      ScriptedSource { frames: frames.into() }
+   }
+}
+
+impl InputSource for ScriptedSource {
+   fn next_frame(&mut self, _live: InputFrame) -> Option<InputFrame> {
+      self.frames.pop_front()
+   }
+}
+
+/*
+ * Plays back a file written by ReplayRecorder. Besides the frames the file
+ * can hold headers, comment lines of the form "# key value", with what's
+ * needed to start the same game again (the seed, the difficulty, ...).
+ */
+pub struct ReplaySource {
+   frames: ScriptedSource,
+   headers: HashMap<String, String>
+}
+
+impl ReplaySource {
+   pub fn load(path: &Path) -> io::Result<Self> {
+      let contents = fs::read_to_string(path)?;
+      let mut frames = Vec::new();
+      let mut headers = HashMap::new();
+
+      for (number, line) in contents.lines().enumerate() {
+         let line = line.trim();
+
+         if let Some(comment) = line.strip_prefix('#') {
+            if let Some((key, value)) = comment.trim().split_once(' ') {
+               headers.insert(String::from(key), String::from(value.trim()));
+            }
+
+            continue;
+         }
+
+         if line.is_empty() {
+            continue;
+         }
+
+         match InputFrame::from_line(line) {
+            Some(frame) => frames.push(frame),
+            None => return Err(io::Error::new(ErrorKind::InvalidData,
+                                              format!("invalid frame on line {}: {}", number + 1, line)))
+         }
+      }
+
+      Ok(ReplaySource { frames: ScriptedSource::new(frames), headers })
+   }
+
+   pub fn header(&self, key: &str) -> Option<&str> {
+      self.headers.get(key).map(|value| value.as_str())
+   }
+}
+
+impl InputSource for ReplaySource {
+   fn next_frame(&mut self, live: InputFrame) -> Option<InputFrame> {
+      self.frames.next_frame(live)
+   }
+}
+
+/*
+ * Wraps another source and writes every frame it produces to a replay file.
+ */
+pub struct ReplayRecorder {
+   source: Box<dyn InputSource>,
+   file: File
+}
+
+impl ReplayRecorder {
+   pub fn create(path: &Path, source: Box<dyn InputSource>, headers: &[(&str, String)]) -> io::Result<Self> {
+      let mut file = File::create(path)?;
+      writeln!(file, "{}", REPLAY_HEADER)?;
+
+      for (key, value) in headers {
+         writeln!(file, "# {} {}", key, value)?;
+      }
+
+      Ok(ReplayRecorder { source, file })
+   }
+}
+
+impl InputSource for ReplayRecorder {
+   fn next_frame(&mut self, live: InputFrame) -> Option<InputFrame> {
+      let frame = self.source.next_frame(live)?;
+
+      if let Err(err) = writeln!(self.file, "{}", frame.to_line()) {
+         eprintln!("Failed to write replay frame: {}", err);
+      }
+
+      Some(frame)
+   }
+}
+
+/*
+ * A player on another machine (see --remote), sending one frame per line
+ * over TCP in the replay file format. The game never waits for the network: if no frame
+ * has arrived for a tick the player keeps moving the way they last did,
+ * without firing.
+ */
+pub struct RemoteSource {
+   reader: BufReader<TcpStream>,
+   line: String,
+   last: InputFrame,
+   connected: bool
+}
+
+impl RemoteSource {
+   pub fn connect<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+      RemoteSource::from_stream(TcpStream::connect(address)?)
+   }
+
+   pub fn from_stream(stream: TcpStream) -> io::Result<Self> {
+      stream.set_nonblocking(true)?;
+      stream.set_nodelay(true)?;
+
+      Ok(RemoteSource {
+         reader: BufReader::new(stream),
+         line: String::new(),
+         last: InputFrame::default(),
+         connected: true
+      })
+   }
+}
+
+impl InputSource for RemoteSource {
+   fn next_frame(&mut self, _live: InputFrame) -> Option<InputFrame> {
+      if !self.connected {
+         return None;
+      }
+
+      // Only one frame is used per tick, any backlog is worked off a tick at a time
+      match self.reader.read_line(&mut self.line) {
+         Ok(0) => {
+            self.connected = false;
+            return None;
+         },
+         Ok(_) if self.line.ends_with('\n') => {
+            match InputFrame::from_line(self.line.trim()) {
+               Some(frame) => self.last = frame,
+               None => eprintln!("Invalid frame from remote player: {}", self.line.trim())
+            }
+
+            self.line.clear();
+            return Some(self.last);
+         },
+         // Partial line, the rest comes later
+         Ok(_) => {},
+         Err(err) if err.kind() == ErrorKind::WouldBlock => {},
+         Err(err) => {
+            eprintln!("Lost connection to remote player: {}", err);
+            self.connected = false;
+            return None;
+         }
+      }
+
+      Some(InputFrame { fire: false, ..self.last })
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use std::net::TcpListener;
+   use std::thread;
+   use std::time::Duration;
+
+   use super::*;
+
+   fn frames(source: &mut dyn InputSource, live: InputFrame) -> Vec<InputFrame> {
+      std::iter::from_fn(|| source.next_frame(live)).collect()
+   }
+
+   #[test]
+   fn frames_round_trip_through_lines() {
+      for frame in [InputFrame::new(-1.0, false), InputFrame::new(0.35, true), InputFrame::default()] {
+         assert_eq!(InputFrame::from_line(&frame.to_line()), Some(frame));
+      }
+
+      assert_eq!(InputFrame::from_line("5 1"), Some(InputFrame::new(1.0, true)));
+      for line in ["", "1", "1 2", "x 0", "NaN 0", "0 0 0"] {
+         assert_eq!(InputFrame::from_line(line), None, "{:?}", line);
+      }
+   }
+
+   #[test]
+   fn live_source_passes_the_player_through() {
+      let live = InputFrame::new(0.5, true);
+      assert_eq!(LiveSource.next_frame(live), Some(live));
+   }
+
+   #[test]
+   fn scripted_source_ignores_the_player_and_runs_out() {
+      let script = vec![InputFrame::new(-1.0, false), InputFrame::new(1.0, true)];
+      let mut source = ScriptedSource::new(script.clone());
+
+      assert_eq!(frames(&mut source, InputFrame::new(0.0, true)), script);
+      assert_eq!(source.next_frame(InputFrame::default()), None);
+   }
+
+   #[test]
+   fn recorded_replay_plays_back_the_same_frames() {
+      let path = std::env::temp_dir().join(format!("space_invaders_replay_{}.txt", std::process::id()));
+      let script = vec![InputFrame::new(-1.0, false), InputFrame::new(0.25, true), InputFrame::new(1.0, false)];
+
+      let mut recorder = ReplayRecorder::create(&path, Box::new(ScriptedSource::new(script.clone())), &[("seed", String::from("42"))]).unwrap();
+      assert_eq!(frames(&mut recorder, InputFrame::default()), script);
+      drop(recorder);
+
+      let mut replay = ReplaySource::load(&path).unwrap();
+      fs::remove_file(&path).unwrap();
+
+      assert_eq!(replay.header("seed"), Some("42"));
+      assert_eq!(replay.header("difficulty"), None);
+      assert_eq!(frames(&mut replay, InputFrame::default()), script);
+   }
+
+   // Polls until the remote source has something other than its idle frame
+   fn next_remote_frame(source: &mut RemoteSource, idle: InputFrame) -> Option<InputFrame> {
+      for _ in 0..1000 {
+         match source.next_frame(InputFrame::default()) {
+            Some(frame) if frame == idle => thread::sleep(Duration::from_millis(1)),
+            other => return other
+         }
+      }
+
+      panic!("no frame from the remote player");
+   }
+
+   #[test]
+   fn remote_source_follows_the_other_side() {
+      let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+      let mut source = RemoteSource::connect(listener.local_addr().unwrap()).unwrap();
+      let (mut stream, _) = listener.accept().unwrap();
+
+      // Nothing sent yet, the player stands still
+      assert_eq!(source.next_frame(InputFrame::new(1.0, true)), Some(InputFrame::default()));
+
+      writeln!(stream, "-1 1").unwrap();
+      assert_eq!(next_remote_frame(&mut source, InputFrame::default()), Some(InputFrame::new(-1.0, true)));
+
+      // Keeps moving without firing until the next frame arrives, even if
+      // only part of it has
+      write!(stream, "0.5").unwrap();
+      stream.flush().unwrap();
+      assert_eq!(source.next_frame(InputFrame::default()), Some(InputFrame::new(-1.0, false)));
+
+      writeln!(stream, " 0").unwrap();
+      assert_eq!(next_remote_frame(&mut source, InputFrame::new(-1.0, false)), Some(InputFrame::new(0.5, false)));
+
+      drop(stream);
+      assert_eq!(next_remote_frame(&mut source, InputFrame::new(0.5, false)), None);
+      assert_eq!(source.next_frame(InputFrame::default()), None);
+   }
+}
@@ -0,0 +1,151 @@
+/*
+ * Describes when the player is awarded extra lives. Every entry in
+ * thresholds is awarded once, in ascending order. If every is set, an
+ * extra life is also awarded each time the score passes another multiple
+ * of it (e.g. every 10000 points in endless play).
+ */
+#[derive(Clone, Debug)]
+pub struct ExtraLifeConfig {
+   pub thresholds: Vec<u64>,
+   pub every: Option<u64>
+}
+
+impl Default for ExtraLifeConfig {
+   fn default() -> Self {
+      ExtraLifeConfig {
+         thresholds: vec![1500],
+         every: None
+      }
+   }
+}
+
+/*
+ * Keeps track of which extra life thresholds were already passed during
+ * the current game.
+ */
+pub struct ExtraLifeTracker {
+   config: ExtraLifeConfig,
+   next_threshold: usize,
+   next_repeat: Option<u64>
+}
+
+impl ExtraLifeTracker {
+   pub fn new(mut config: ExtraLifeConfig) -> Self {
+      config.thresholds.sort_unstable();
+      config.thresholds.dedup();
+
+      let next_repeat = config.every.filter(|every| *every > 0);
+
+      ExtraLifeTracker {
+         config,
+         next_threshold: 0,
+         next_repeat
+      }
+   }
+
+   pub fn reset(&mut self) {
+      self.next_threshold = 0;
+      self.next_repeat = self.config.every.filter(|every| *every > 0);
+   }
+
+   /*
+    * Returns how many extra lives were earned since the last call for the
+    * given score. Usually 0 or 1, but a big jump in score can cross more
+    * than one threshold at once.
+    */
+   pub fn check(&mut self, score: u64) -> u32 {
+      let mut awarded = 0;
+
+      while self.next_threshold < self.config.thresholds.len() &&
+            score >= self.config.thresholds[self.next_threshold] {
+         self.next_threshold += 1;
+         awarded += 1;
+      }
+
+      if let Some(every) = self.config.every.filter(|every| *every > 0) {
+         while let Some(next) = self.next_repeat {
+            if score < next {
+               break;
+            }
+
+            awarded += 1;
+            self.next_repeat = next.checked_add(every);
+         }
+      }
+
+      awarded
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn tracker(thresholds: &[u64], every: Option<u64>) -> ExtraLifeTracker {
+      ExtraLifeTracker::new(ExtraLifeConfig { thresholds: thresholds.to_vec(), every })
+   }
+
+   #[test]
+   fn threshold_is_awarded_when_reached_exactly() {
+      let mut tracker = tracker(&[1500], None);
+
+      assert_eq!(tracker.check(1490), 0);
+      assert_eq!(tracker.check(1500), 1);
+      assert_eq!(tracker.check(1510), 0);
+   }
+
+   #[test]
+   fn every_threshold_is_awarded_only_once() {
+      let mut tracker = tracker(&[1500], None);
+
+      assert_eq!(tracker.check(2000), 1);
+      assert_eq!(tracker.check(2000), 0);
+      assert_eq!(tracker.check(1000000), 0);
+   }
+
+   #[test]
+   fn one_jump_can_cross_several_thresholds() {
+      // Unsorted and with a duplicate, like a hand edited config
+      let mut tracker = tracker(&[5000, 1000, 3000, 1000], None);
+
+      assert_eq!(tracker.check(3500), 2);
+      assert_eq!(tracker.check(6000), 1);
+      assert_eq!(tracker.check(9000), 0);
+   }
+
+   #[test]
+   fn repeats_keep_coming_every_so_many_points() {
+      let mut tracker = tracker(&[1500], Some(10000));
+
+      assert_eq!(tracker.check(1500), 1);
+      assert_eq!(tracker.check(9990), 0);
+      assert_eq!(tracker.check(10000), 1);
+      assert_eq!(tracker.check(35000), 2);
+      assert_eq!(tracker.check(39990), 0);
+   }
+
+   #[test]
+   fn repeating_every_zero_points_is_off() {
+      let mut tracker = tracker(&[], Some(0));
+
+      assert_eq!(tracker.check(0), 0);
+      assert_eq!(tracker.check(u64::MAX), 0);
+   }
+
+   #[test]
+   fn repeats_stop_at_the_highest_score() {
+      let mut tracker = tracker(&[], Some(u64::MAX / 2 + 1));
+
+      assert_eq!(tracker.check(u64::MAX), 1);
+      assert_eq!(tracker.check(u64::MAX), 0);
+   }
+
+   #[test]
+   fn reset_starts_over() {
+      let mut tracker = tracker(&[1500], Some(10000));
+
+      assert_eq!(tracker.check(20000), 3);
+      tracker.reset();
+      assert_eq!(tracker.check(20000), 3);
+   }
+}
@@ -1,312 +1,416 @@
+mod achievements;
+mod audio;
+mod bot;
+mod capture;
+mod cli;
+mod config;
+#[cfg(feature = "dev")]
+mod console;
+mod debug_overlay;
+mod difficulty;
 mod entities;
+mod events;
+mod gamepad;
 mod input;
+mod input_source;
 mod state;
 mod init;
+mod lives;
+mod menu;
+mod raster;
+mod render;
+mod timestep;
+mod viewport;
+mod game;
+mod gym;
+mod headless;
+mod hot_reload;
+mod highscores;
+mod scenes;
+mod settings;
+mod sprites;
+mod simulation;
+mod statistics;
+mod snapshots;
+mod software;
+mod toast;
+mod tui;
+
+use std::path::Path;
+use std::{env, fs, process};
+use std::time::Instant;
+
+use clap::Parser;
 
-use std::path::{MAIN_SEPARATOR, Path};
-use std::{env, path, fs};
-
-use ggez::glam::Vec2;
 use ggez::{Context, GameResult};
-use ggez::graphics::{self, Image, DrawParam, Canvas, Drawable};
-use ggez::event::{self};
-use ggez::conf::{Conf, WindowMode};
-use state::State;
-use std::fs::{OpenOptions};
-use std::io::{prelude::*};
-use std::os::windows::prelude::*;
-use winapi::um::winnt::FILE_ATTRIBUTE_HIDDEN;
-
-const WINDOW_SIZE_HEIGHT: f32 = 1080.0;
-const WINDOW_SIZE_WIDTH: f32 = 1920.0;
+use ggez::glam::Vec2;
+use ggez::graphics::{self, Image};
+use ggez::event::{self, Axis, Button, GamepadId, MouseButton};
+use ggez::conf::{Conf, FullscreenType, WindowMode};
+use ggez::input::keyboard::{KeyCode, KeyInput, KeyMods};
+use bot::Autopilot;
+use capture::Capture;
+use cli::Cli;
+use config::GameConfig;
+#[cfg(feature = "dev")]
+use console::Console;
+use debug_overlay::DebugOverlay;
+use difficulty::Difficulty;
+use events::EventLog;
+use game::Game;
+use gamepad::GamepadSlots;
+use headless::Pilot;
+use input::{Action, InputEvent};
+use input_source::{LiveSource, RemoteSource, ReplaySource};
+use scenes::{Playing, SceneStack, Title};
+use settings::UserSettings;
+use simulation::GameOptions;
+use sprites::SpriteManifest;
+use timestep::{FixedTimestep, TICKS_PER_SECOND};
+use viewport::Viewport;
+
+const MIN_WINDOW_WIDTH: f32 = 480.0;
+const MIN_WINDOW_HEIGHT: f32 = 270.0;
 
 struct MainState{
-    count: u64,
-    state: Box<state::State>,
-    state_copy: Box<state::State>,
-    is_on_starting_screen: bool,
-    is_game_over: bool,
-    score: u64,
-    high_score: u64,
-    sprite_sheet: Image,
-    score_file: String,
-    player_starting_coords: Vec2
+    game: Game,
+    scenes: SceneStack,
+    timestep: FixedTimestep,
+    gamepads: GamepadSlots,
+    capture: Capture,
+    debug_overlay: DebugOverlay,
+    #[cfg(feature = "dev")]
+    console: Console
 }
 
 impl MainState {
-    pub fn new(_ctx: &mut Context, sprite_sheet: Image, score_file: &str) -> Self {
-        let score_file_path = Path::new(score_file);
+    pub fn new(game: Game, capture: Capture) -> Self {
+        MainState {
+            game,
+            scenes: SceneStack::new(Box::new(Title::new())),
+            timestep: FixedTimestep::new(TICKS_PER_SECOND),
+            gamepads: GamepadSlots::default(),
+            capture,
+            debug_overlay: DebugOverlay::default(),
+            #[cfg(feature = "dev")]
+            console: Console::default()
+        }
+    }
+
+    fn handle_input_events(&mut self, ctx: &mut Context, events: Vec<InputEvent>) {
+        for event in events {
+            match event {
+                InputEvent::PadConnected(slot) => {
+                    let name = self.gamepads.name(slot).unwrap_or("GAMEPAD").to_uppercase();
+                    self.game.toasts.push(&format!("{}  CONNECTED", name));
+                },
+                InputEvent::PadDisconnected(slot) => {
+                    let name = self.gamepads.name(slot).unwrap_or("GAMEPAD").to_uppercase();
+                    self.game.toasts.push(&format!("{}  DISCONNECTED", name));
+                },
+                _ => {}
+            }
 
-        let mut high_score = 0;
-        if score_file_path.exists() {
-            let high_score_string = fs::read_to_string(score_file_path);
+            // Don't let the game run on without a controller
+            if event.loses_focus() && !self.scenes.focus_lost(&mut self.game) {
+                ctx.request_quit();
+            }
+
+            self.game.handle_input(event);
+        }
+    }
+}
+
+impl event::EventHandler for MainState {
+    fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let events = self.gamepads.poll(ctx);
+        self.handle_input_events(ctx, events);
 
+        // Edits to the config and sprites take effect from the next tick on
+        self.game.reload_changed_files(ctx.time.delta().as_secs_f32());
 
-            if let Err(err) = high_score_string {
-                println!("Failed to read file {}: {}", score_file, err.to_string());
-            } else {
-                match high_score_string.unwrap().parse::<u64>() {
-                    Err(err) => { println!("Failed to parse high score? {}", err) },
-                    Ok(num) => { high_score = num; }
-                }
+        self.timestep.advance(ctx.time.delta());
+
+        while self.timestep.tick() {
+            let started = Instant::now();
+
+            if self.game.input.take(Action::Mute) {
+                self.game.toggle_mute();
             }
-        } else {
-            // For windows we must set the hidden attribute ourselves
-            if std::env::consts::OS == "windows" {
-                let file = OpenOptions::new()
-                           .write(true)
-                           .create(true)
-                           .attributes(FILE_ATTRIBUTE_HIDDEN)
-                           .open(score_file);
-
-                if let Err(err) = file {
-                    println!("Failed to create high score file: {}", err.to_string());
-                }
-            } else {
-                let file = OpenOptions::new()
-                           .write(true)
-                           .create(true)
-                           .open(score_file);
-
-                if let Err(err) = file {
-                    println!("Failed to create high score file: {}", err.to_string());
-                }
+
+            if !self.scenes.update(&mut self.game, ctx, self.timestep.dt()) {
+                ctx.request_quit();
             }
 
+            self.debug_overlay.record_tick(started.elapsed());
+
+            self.game.toasts.update();
+
+            // Presses that the current scene didn't use shouldn't leak into the next one
+            self.game.input.clear_presses();
         }
 
-        let enemies = init::init_enemies(&sprite_sheet,
-                                                    7,
-                                                    Vec2 { x: 0.55, y: 0.55 },
-                                                    WINDOW_SIZE_WIDTH,
-                                                    WINDOW_SIZE_HEIGHT);
+        Ok(())
+    }
 
-        let player = init::init_player(&sprite_sheet,
-                                                    Vec2 { x: 1.0, y: 1.0 },
-                                                    WINDOW_SIZE_WIDTH,
-                                                    WINDOW_SIZE_HEIGHT);
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let (window_width, window_height) = ctx.gfx.drawable_size();
+        let viewport = Viewport::new(window_width, window_height);
 
-        let starting_coords = player.get_coords();
+        // The last frame is still in the window until the canvas clears it
+        self.capture.update(ctx, &viewport, ctx.time.delta().as_secs_f32(), &mut self.game.toasts);
 
-        MainState {
-            count: 0,
-            state: Box::new( State::new(21, enemies.clone(), player.clone())),
-            state_copy: Box::new( State::new(21, enemies, player)),
-            is_on_starting_screen: true,
-            is_game_over: false,
-            score: 0,
-            high_score: high_score,
-            sprite_sheet: sprite_sheet,
-            score_file: String::from(score_file),
-            player_starting_coords: starting_coords
+        let black = graphics::Color::from_rgb(0, 0, 0);
+        let mut canvas = graphics::Canvas::from_frame(ctx, black);
+
+        // Everything is drawn in logical playfield units from here on
+        viewport.apply(&mut canvas);
+
+        self.scenes.draw(&mut self.game, &mut canvas, ctx, self.timestep.alpha());
+        self.debug_overlay.draw(&mut canvas, ctx, &self.game.sim);
+        self.game.toasts.draw(&mut canvas, ctx);
+
+        #[cfg(feature = "dev")]
+        self.console.draw(&mut canvas);
+
+        canvas.finish(ctx)?;
+        Ok(())
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, repeated: bool) -> GameResult<()> {
+        if repeated {
+            return Ok(());
+        }
+
+        #[cfg(feature = "dev")]
+        if self.console.key_down(input.keycode, &mut self.game) {
+            return Ok(());
         }
+
+        if let Some(key) = input.keycode {
+            self.game.handle_input(InputEvent::KeyDown(key));
+        }
+
+        match input.keycode {
+            Some(KeyCode::F11) => self.game.toggle_fullscreen(ctx),
+            Some(KeyCode::F12) => self.capture.request_screenshot(),
+            Some(KeyCode::F9) => self.capture.toggle_recording(&mut self.game.toasts),
+            Some(KeyCode::F3) => self.debug_overlay.toggle(),
+            Some(KeyCode::Return) if input.mods.contains(KeyMods::ALT) => self.game.toggle_fullscreen(ctx),
+            _ => {}
+        }
+
+        Ok(())
     }
-}
 
-impl MainState {
-    pub fn draw_text(&mut self, canvas: &mut Canvas, ctx: &mut Context, is_game_over: bool, is_on_starting_screen: bool) {
-        if is_on_starting_screen {
-            let mut title = graphics::Text::new("SPACE INVADERS");
-            title.set_font("MainFont");
-            title.set_scale(graphics::PxScale::from(150.0));
-            canvas.draw(&title, DrawParam::default().dest(Vec2::new(
-                (WINDOW_SIZE_WIDTH - title.dimensions(ctx).unwrap().w) / 2.0,
-                (WINDOW_SIZE_HEIGHT - title.dimensions(ctx).unwrap().h) / 3.0)));
-
-            let mut desc_1 = graphics::Text::new("Move  with  ARROW  KEYS");
-            desc_1.set_font("MainFont");
-            desc_1.set_scale(graphics::PxScale::from(60.0));
-            canvas.draw(&desc_1, DrawParam::default().dest(Vec2::new(
-                (WINDOW_SIZE_WIDTH - desc_1.dimensions(ctx).unwrap().w) / 2.0,
-                (WINDOW_SIZE_HEIGHT - desc_1.dimensions(ctx).unwrap().h) / 2.0)));
-
-            let mut desc_2 = graphics::Text::new("Shoot  with  SPACEBAR");
-            desc_2.set_font("MainFont");
-            desc_2.set_scale(graphics::PxScale::from(60.0));
-            canvas.draw(&desc_2, DrawParam::default().dest(Vec2::new(
-                (WINDOW_SIZE_WIDTH - desc_2.dimensions(ctx).unwrap().w) / 2.0,
-                (WINDOW_SIZE_HEIGHT - desc_2.dimensions(ctx).unwrap().h) / 1.8)));
-
-            let mut desc_3 = graphics::Text::new("PRESS  SPACEBAR  TO  START");
-            desc_3.set_font("MainFont");
-            desc_3.set_scale(graphics::PxScale::from(70.0));
-            canvas.draw(&desc_3, DrawParam::default().dest(Vec2::new(
-                (WINDOW_SIZE_WIDTH - desc_3.dimensions(ctx).unwrap().w) / 2.0,
-                (WINDOW_SIZE_HEIGHT - desc_3.dimensions(ctx).unwrap().h) / 1.5)));
-        } else if is_game_over {
-            let mut game_over = graphics::Text::new("GAME OVER");
-            game_over.set_font("MainFont");
-            game_over.set_scale(graphics::PxScale::from(150.0));
-            canvas.draw(&game_over, DrawParam::default().dest(Vec2::new(
-                (WINDOW_SIZE_WIDTH - game_over.dimensions(ctx).unwrap().w) / 2.0,
-                (WINDOW_SIZE_HEIGHT - game_over.dimensions(ctx).unwrap().h) / 3.0)));
-
-            let mut restart = graphics::Text::new("PRESS  SPACEBAR  TO  RESTART");
-            restart.set_font("MainFont");
-            restart.set_scale(graphics::PxScale::from(70.0));
-            canvas.draw(&restart, DrawParam::default().dest(Vec2::new(
-                (WINDOW_SIZE_WIDTH - restart.dimensions(ctx).unwrap().w) / 2.0,
-                (WINDOW_SIZE_HEIGHT - restart.dimensions(ctx).unwrap().h) / 1.5)));
-        } else {
-            let mut lives = graphics::Text::new(format!("LIVES  {}", self.state.get_player_lives()));
-            lives.set_font("MainFont");
-            lives.set_scale(graphics::PxScale::from(40.0));
-
-            canvas.draw(&lives, DrawParam::default().dest(Vec2::new(
-                WINDOW_SIZE_WIDTH / 20.0,
-                WINDOW_SIZE_HEIGHT / 50.0
-            )));
-
-            let mut score = graphics::Text::new(format!("SCORE  {}", self.score));
-            score.set_font("MainFont");
-            score.set_scale(graphics::PxScale::from(40.0));
-
-            canvas.draw(&score, DrawParam::default().dest(Vec2::new(
-                WINDOW_SIZE_WIDTH / 1.5,
-                WINDOW_SIZE_HEIGHT / 50.0
-            )));
-
-            let mut high_score = graphics::Text::new(format!("HIGH  SCORE  {}", self.high_score));
-            high_score.set_font("MainFont");
-            high_score.set_scale(graphics::PxScale::from(40.0));
-
-            canvas.draw(&high_score, DrawParam::default().dest(Vec2::new(
-                WINDOW_SIZE_WIDTH - high_score.dimensions(ctx).unwrap().w * 1.5,
-                WINDOW_SIZE_HEIGHT / 50.0
-            )))
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) -> GameResult<()> {
+        if button == MouseButton::Left && self.debug_overlay.is_visible() {
+            let (window_width, window_height) = ctx.gfx.drawable_size();
+            let point = Viewport::new(window_width, window_height).to_logical(Vec2::new(x, y));
+            self.debug_overlay.select(&self.game.sim, point);
         }
+
+        Ok(())
     }
-}
 
-impl event::EventHandler for MainState {
-    fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
-        if self.is_on_starting_screen {
-            if input::is_space_pressed(ctx) {
-                self.is_on_starting_screen = false;
-            }
-        } else if !self.is_game_over {
-            self.count += 1;
-            let image_dimensions = Vec2 { x: self.sprite_sheet.height() as f32, y: self.sprite_sheet.width() as f32};
+    #[cfg(feature = "dev")]
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) -> GameResult<()> {
+        self.console.type_char(character);
+        Ok(())
+    }
 
-            self.is_game_over = !self.state.is_player_alive();
-            self.state.check_if_enemy_shot_player(image_dimensions.clone());
+    fn key_up_event(&mut self, _ctx: &mut Context, input: KeyInput) -> GameResult<()> {
+        if let Some(key) = input.keycode {
+            self.game.handle_input(InputEvent::KeyUp(key));
+        }
 
-            if self.state.check_if_player_shot_enemy(image_dimensions.clone()) {
-                self.score += 10;
-            }
+        Ok(())
+    }
 
-            if input::update_shoot(ctx) {
-                self.state.player_shoot();
-            }
+    fn gamepad_button_down_event(&mut self, ctx: &mut Context, button: Button, id: GamepadId) -> GameResult<()> {
+        let mut events = Vec::new();
+        let slot = self.gamepads.slot(id, &mut events);
+        events.push(InputEvent::ButtonDown(slot, button));
+        self.handle_input_events(ctx, events);
 
-            let direction = input::update_movement(ctx);
-            self.state.update(6.0 * direction as f32, image_dimensions, true);
+        Ok(())
+    }
 
-            self.state.enemies_shoot();
+    fn gamepad_button_up_event(&mut self, ctx: &mut Context, button: Button, id: GamepadId) -> GameResult<()> {
+        let mut events = Vec::new();
+        let slot = self.gamepads.slot(id, &mut events);
+        events.push(InputEvent::ButtonUp(slot, button));
+        self.handle_input_events(ctx, events);
 
-            if self.state.all_enemies_dead() {
-                self.load_next_wave(1);
-            }
+        Ok(())
+    }
 
-        } else {
-            self.restart(ctx);
-        }
+    fn gamepad_axis_event(&mut self, ctx: &mut Context, axis: Axis, value: f32, id: GamepadId) -> GameResult<()> {
+        let mut events = Vec::new();
+        let slot = self.gamepads.slot(id, &mut events);
+        events.push(InputEvent::AxisMoved(slot, axis, value));
+        self.handle_input_events(ctx, events);
 
         Ok(())
     }
 
-    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
-        let black = graphics::Color::from_rgb(0, 0, 0);
-        let mut canvas = graphics::Canvas::from_frame(ctx, black);
+    fn focus_event(&mut self, ctx: &mut Context, gained: bool) -> GameResult<()> {
+        if !gained {
+            // The key up events for anything held now go to another window
+            self.game.input.release_all();
 
-        if !self.is_game_over && !self.is_on_starting_screen {
-            self.state.draw_enemies(&mut canvas, self.count, &self.sprite_sheet);
-            self.state.draw_player(&mut canvas, self.count, &self.sprite_sheet);
+            if !self.scenes.focus_lost(&mut self.game) {
+                ctx.request_quit();
+            }
         }
-        self.draw_text(&mut canvas, ctx, self.is_game_over, self.is_on_starting_screen);
 
-        canvas.finish(ctx)?;
         Ok(())
     }
 }
 
-impl MainState {
-    pub fn write_high_score(&self) {
-        let path = Path::new(self.score_file.as_str());
+pub fn main() {
+    let cli = Cli::parse();
 
-        if path.exists() {
-            let mut file = OpenOptions::new()
-                           .write(true)
-                           .open(path).unwrap();
+    let resource_dir = cli.resources.clone().unwrap_or_else(|| {
+        if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+            Path::new(&manifest_dir).join("src").join("resources")
+        } else {
+            Path::new(".").join("src").join("resources")
+        }
+    });
 
-            let result = file.write(self.high_score.to_string().as_bytes());
+    let data_dir = cli.data_dir.clone().unwrap_or_else(|| resource_dir.clone());
+    if let Err(err) = fs::create_dir_all(&data_dir) {
+        eprintln!("Failed to create data directory {}: {}", data_dir.display(), err);
+        return;
+    }
+
+    // A config that was asked for has to work, the default one may be missing
+    let config_path = cli.config.clone().unwrap_or_else(|| resource_dir.join("config.toml"));
+    let config = match load_or_default(&config_path, cli.config.is_some(), GameConfig::load) {
+        Some(config) => config,
+        None => return
+    };
+
+    let sprites_path = resource_dir.join("sprites.toml");
+    let sprites = match load_or_default(&sprites_path, false, SpriteManifest::load) {
+        Some(sprites) => sprites,
+        None => return
+    };
 
-            if let Err(err) = result {
-                eprintln!("Failed to write high score: {}", err.to_string());
+    let mut options = GameOptions {
+        difficulty: cli.difficulty,
+        start_wave: cli.start_wave
+    };
+    let mut seed = cli.seed;
+
+    // A replay only plays out the same way with the settings it was recorded with
+    let replay = match cli.replay.as_ref().map(|path| (path, ReplaySource::load(path))) {
+        None => None,
+        Some((path, Err(err))) => {
+            eprintln!("Failed to load replay {}: {}", path.display(), err);
+            return;
+        },
+        Some((_, Ok(replay))) => {
+            if let Some(replay_seed) = replay.header("seed").and_then(|value| value.parse().ok()) {
+                seed = Some(replay_seed);
+            }
+            if let Some(difficulty) = replay.header("difficulty").and_then(Difficulty::from_name) {
+                options.difficulty = difficulty;
+            }
+            if let Some(start_wave) = replay.header("start_wave").and_then(|value| value.parse().ok()) {
+                options.start_wave = start_wave;
             }
 
-            // if let Err(err) = file {
-            //     eprintln!("Failed to open file {}, reason: {}", path.as_os_str().to_str().unwrap(), err.to_string());
-            // } else {
-            //     // file.unwrap().write(self.high_score.to_string().as_bytes());;
-            //     file = file.unwrap();
+            Some(replay)
+        }
+    };
 
+    let remote = match cli.remote.as_ref().map(|address| (address, RemoteSource::connect(address.as_str()))) {
+        None => None,
+        Some((address, Err(err))) => {
+            eprintln!("Failed to connect to remote player at {}: {}", address, err);
+            return;
+        },
+        Some((_, Ok(remote))) => Some(remote)
+    };
 
-            }
-            // let err = fs::write(path, self.high_score.to_string());
-            // println!("{}", path.as_os_str().to_str().unwrap());
-            // if let Err(e) = err {
-            //     eprint!("Couldn't write high score to file: {}", e.to_string());
-            // }
+    let event_log = match cli.event_log.as_ref().map(|path| (path, EventLog::create(path))) {
+        None => None,
+        Some((path, Err(err))) => {
+            eprintln!("Failed to create event log {}: {}", path.display(), err);
+            return;
+        },
+        Some((_, Ok(event_log))) => Some(event_log)
+    };
+
+    let high_score_file_path = data_dir.join(game::SCORE_FILE).to_string_lossy().into_owned();
+
+    if let Some(dir) = cli.snapshot.as_ref() {
+        if let Err(err) = snapshots::write(&resource_dir, dir) {
+            eprintln!("{err}");
         }
+        return;
+    }
 
-    pub fn restart(&mut self, ctx: &mut Context) {
-        if input::is_space_pressed(ctx) {
-            self.state.set_enemies(self.state_copy.get_enemies());
-            self.state.revive_player(self.player_starting_coords);
-            state::reset_enemy_movement_speed();
-            state::set_enemy_direction_right();
-            if self.score > self.high_score {
-                self.high_score = self.score;
-                self.write_high_score();
+    if let Some(dir) = cli.verify_snapshots.as_ref() {
+        match snapshots::verify(&resource_dir, dir) {
+            Ok(true) => return,
+            Ok(false) => process::exit(1),
+            Err(err) => {
+                eprintln!("{err}");
+                process::exit(1);
             }
+        }
+    }
 
-            self.count = 0;
-            self.score = 0;
-            self.is_game_over = false
+    if cli.tui {
+        let controls = UserSettings::load(&data_dir.join(game::SETTINGS_FILE)).controls;
+
+        if let Err(err) = tui::run(&resource_dir, config, sprites, options, seed, &high_score_file_path, controls) {
+            eprintln!("{err}");
         }
+        return;
     }
 
-    pub fn load_next_wave(&mut self, enemy_speed: u8) {
-        self.state.set_enemies(self.state_copy.get_enemies());
-        state::set_enemy_direction_right();
-        state::increase_enemy_movement_speed(enemy_speed);
+    if cli.env {
+        if let Err(err) = gym::run(&resource_dir, config, sprites, options, cli.frame_skip, cli.env_frames) {
+            eprintln!("{err}");
+        }
+        return;
+    }
+
+    if cli.headless {
+        let pilot = match replay {
+            Some(replay) => Pilot::Input(Box::new(replay)),
+            None if cli.bot => Pilot::Bot(Autopilot::new()),
+            None => Pilot::Input(Box::new(LiveSource))
+        };
+
+        let max_ticks = cli.max_ticks.unwrap_or(headless::MAX_TICKS);
+
+        if let Err(err) = headless::run(&resource_dir, config, sprites, options, seed.unwrap_or_else(rand::random), pilot, max_ticks, event_log) {
+            eprintln!("{err}");
+        }
+        return;
     }
-}
 
-pub fn main() {
     // Конфигурация:
+    let (width, height) = cli.resolution;
     let conf = Conf::new().
         window_mode(WindowMode {
-            width: WINDOW_SIZE_WIDTH,
-            height: WINDOW_SIZE_HEIGHT,
-            min_width: WINDOW_SIZE_WIDTH,
-            min_height: WINDOW_SIZE_HEIGHT,
+            width,
+            height,
+            min_width: MIN_WINDOW_WIDTH,
+            min_height: MIN_WINDOW_HEIGHT,
+            resizable: true,
+            fullscreen_type: if cli.fullscreen { FullscreenType::Desktop } else { FullscreenType::Windowed },
             ..Default::default()
         });
 
-    let resource_dir = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
-        let mut path = path::PathBuf::from(manifest_dir);
-        path.push("src");
-        path.push("resources");
-        path
-    } else {
-        path::PathBuf::from(format!(".{}src{}resources", MAIN_SEPARATOR, MAIN_SEPARATOR))
-    };
-
     let cb = ggez::ContextBuilder::new("Space Invaders", "Stanislav Hristov").
                              default_conf(conf).
-                             add_resource_path(resource_dir.clone());
+                             add_resource_path(resource_dir);
 
     let (mut ctx, event_loop) = cb.build().unwrap();
 
@@ -321,10 +425,52 @@ pub fn main() {
 
     init::init_font(&mut ctx, "/font.TTF");
 
-    let mut high_score_file_path = String::from(resource_dir.into_os_string().to_str().unwrap());
-    high_score_file_path += format!("{}.high_score.txt", MAIN_SEPARATOR).as_str();
+    let mut game = Game::new(image.unwrap(), &data_dir, config, sprites, options);
+    game.watch_files(&config_path, &sprites_path);
+    game.fullscreen = cli.fullscreen;
+    game.seed = seed;
+    game.record_path = cli.record;
+    game.event_log = event_log;
+
+    let mut state = MainState::new(game, Capture::new(&data_dir, cli.clip_seconds));
+
+    // Straight into the replay, back to the title screen once the game is over
+    if let Some(replay) = replay {
+        state.game.reset();
+        state.game.input_source = Box::new(replay);
+        state.scenes.push(Box::new(Playing::new()));
+    }
+
+    // Same for the remote player, who keeps playing until they disconnect
+    if let Some(remote) = remote {
+        state.game.reset();
+        state.game.input_source = Box::new(remote);
+        state.scenes.push(Box::new(Playing::new()));
+    }
 
     // Пускане на главния loop
-    let state = MainState::new(&mut ctx, image.unwrap(), high_score_file_path.as_str());
     event::run(ctx, event_loop, state);
-}
\ No newline at end of file
+}
+
+/*
+ * Loads a file the game can also do without. A required file has to load,
+ * otherwise a missing or broken file falls back to the defaults. Returns
+ * None if the game can't start.
+ */
+fn load_or_default<T: Default>(path: &Path, required: bool, load: fn(&Path) -> Result<T, String>) -> Option<T> {
+    if !required && !path.exists() {
+        return Some(T::default());
+    }
+
+    match load(path) {
+        Ok(value) => Some(value),
+        Err(err) if required => {
+            eprintln!("{err}");
+            None
+        },
+        Err(err) => {
+            eprintln!("{err}, using defaults");
+            Some(T::default())
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use ggez::Context;
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, Color};
+
+use crate::render::{GpuRenderer, Renderer};
+use crate::viewport::PLAYFIELD_WIDTH;
+
+const ITEM_SCALE: f32 = 60.0;
+const ITEM_SPACING: f32 = 90.0;
+
+/*
+ * A vertical list of text items, one of which is selected. The owner decides
+ * what selecting an item does, the menu only keeps track of the cursor.
+ */
+pub struct Menu {
+   items: Vec<String>,
+   selected: usize,
+   scale: f32,
+   spacing: f32
+}
+
+impl Menu {
+   pub fn new(items: &[&str]) -> Self {
+      Menu {
+         items: items.iter().map(|item| String::from(*item)).collect(),
+         selected: 0,
+         scale: ITEM_SCALE,
+         spacing: ITEM_SPACING
+      }
+   }
+
+   /*
+    * Smaller text and spacing, for menus with a lot of items.
+    */
+   pub fn with_layout(mut self, scale: f32, spacing: f32) -> Self {
+      self.scale = scale;
+      self.spacing = spacing;
+      self
+   }
+
+   pub fn selected(&self) -> usize {
+      self.selected
+   }
+
+   /*
+    * Moves the cursor up (negative) or down (positive), wrapping around.
+    */
+   pub fn move_cursor(&mut self, direction: i8) {
+      if self.items.is_empty() || direction == 0 {
+         return;
+      }
+
+      let len = self.items.len();
+      self.selected = if direction < 0 {
+         (self.selected + len - 1) % len
+      } else {
+         (self.selected + 1) % len
+      };
+   }
+
+   pub fn set_item(&mut self, index: usize, label: &str) {
+      if let Some(item) = self.items.get_mut(index) {
+         *item = String::from(label);
+      }
+   }
+
+   /*
+    * Draws the items centered horizontally, starting at top (in logical units).
+    */
+   pub fn draw(&self, canvas: &mut Canvas, ctx: &mut Context, top: f32) {
+      self.render(&mut GpuRenderer::new(canvas, ctx), top);
+   }
+
+   pub fn render(&self, renderer: &mut dyn Renderer, top: f32) {
+      for (index, item) in self.items.iter().enumerate() {
+         let label = if index == self.selected { format!(">  {}  <", item) } else { item.clone() };
+         let color = if index == self.selected { Color::from_rgb(0, 255, 0) } else { Color::WHITE };
+         let size = renderer.text_size(&label, self.scale);
+
+         renderer.draw_text(&label, self.scale, Vec2::new(
+            (PLAYFIELD_WIDTH - size.x) / 2.0,
+            top + index as f32 * self.spacing
+         ), color);
+      }
+   }
+}
@@ -0,0 +1,44 @@
+use ggez::graphics::Rect;
+
+use crate::viewport::{PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT};
+
+/*
+ * A coarse picture of the playfield, each cell covering a block of it. Used
+ * by frontends that draw without a GPU.
+ */
+pub struct Grid<T> {
+   pub width: usize,
+   pub height: usize,
+   pub cells: Vec<T>
+}
+
+impl<T: Copy> Grid<T> {
+   pub fn new(width: usize, height: usize, empty: T) -> Self {
+      Grid {
+         width,
+         height,
+         cells: vec![empty; width * height]
+      }
+   }
+
+   pub fn get(&self, x: usize, y: usize) -> T {
+      self.cells[y * self.width + x]
+   }
+
+   /*
+    * Sets every cell bounds (in playfield units) touches to value.
+    */
+   pub fn fill(&mut self, bounds: Rect, value: T) {
+      let scale_x = self.width as f32 / PLAYFIELD_WIDTH;
+      let scale_y = self.height as f32 / PLAYFIELD_HEIGHT;
+
+      let left = ((bounds.x * scale_x).max(0.0) as usize).min(self.width);
+      let top = ((bounds.y * scale_y).max(0.0) as usize).min(self.height);
+      let right = (((bounds.x + bounds.w) * scale_x).ceil().max(0.0) as usize).clamp(left, self.width);
+      let bottom = (((bounds.y + bounds.h) * scale_y).ceil().max(0.0) as usize).clamp(top, self.height);
+
+      for y in top..bottom {
+         self.cells[y * self.width + left..y * self.width + right].fill(value);
+      }
+   }
+}
@@ -0,0 +1,156 @@
+use ggez::Context;
+use ggez::glam::Vec2;
+use ggez::graphics::{self, Canvas, Color, DrawParam, Drawable, Image};
+
+use crate::init;
+use crate::simulation::Simulation;
+use crate::viewport::{PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT};
+
+/*
+ * Something the game can be drawn on, in playfield units: the window (see
+ * GpuRenderer) or an image in memory (see software.rs). Code that should
+ * look the same on both draws through this instead of a ggez Canvas.
+ */
+pub trait Renderer {
+   // A part of the sprite sheet, param.src is the part as fractions of it
+   fn draw_sprite(&mut self, param: DrawParam);
+
+   // Text in the game font, position is its top left corner
+   fn draw_text(&mut self, text: &str, scale: f32, position: Vec2, color: Color);
+
+   fn text_size(&mut self, text: &str, scale: f32) -> Vec2;
+}
+
+/*
+ * Draws on the window's canvas. Screens that only draw text don't need the
+ * sprite sheet.
+ */
+pub struct GpuRenderer<'a> {
+   canvas: &'a mut Canvas,
+   ctx: &'a mut Context,
+   sprite_sheet: Option<&'a Image>
+}
+
+impl<'a> GpuRenderer<'a> {
+   pub fn new(canvas: &'a mut Canvas, ctx: &'a mut Context) -> Self {
+      GpuRenderer {
+         canvas,
+         ctx,
+         sprite_sheet: None
+      }
+   }
+
+   pub fn with_sprite_sheet(mut self, sprite_sheet: &'a Image) -> Self {
+      self.sprite_sheet = Some(sprite_sheet);
+      self
+   }
+}
+
+impl Renderer for GpuRenderer<'_> {
+   fn draw_sprite(&mut self, param: DrawParam) {
+      if let Some(sprite_sheet) = self.sprite_sheet {
+         self.canvas.draw(sprite_sheet, param);
+      }
+   }
+
+   fn draw_text(&mut self, text: &str, scale: f32, position: Vec2, color: Color) {
+      self.canvas.draw(&text_in_font(text, scale), DrawParam::default().dest(position).color(color));
+   }
+
+   fn text_size(&mut self, text: &str, scale: f32) -> Vec2 {
+      let dimensions = text_in_font(text, scale).dimensions(self.ctx).unwrap();
+      Vec2::new(dimensions.w, dimensions.h)
+   }
+}
+
+/*
+ * Text in the game font, with the characters it has no glyphs for in the
+ * fallback font instead of boxes (see init::has_glyph).
+ */
+pub fn text_in_font(text: &str, scale: f32) -> graphics::Text {
+   let mut result = graphics::Text::default();
+   let mut run = String::new();
+   let mut run_has_glyphs = true;
+
+   for c in text.chars() {
+      let has_glyph = init::has_glyph(c);
+      if has_glyph != run_has_glyphs && !run.is_empty() {
+         result.add(font_run(std::mem::take(&mut run), run_has_glyphs));
+      }
+
+      run_has_glyphs = has_glyph;
+      run.push(c);
+   }
+
+   if !run.is_empty() {
+      result.add(font_run(run, run_has_glyphs));
+   }
+
+   result.set_scale(graphics::PxScale::from(scale));
+   result
+}
+
+fn font_run(text: String, has_glyphs: bool) -> graphics::TextFragment {
+   graphics::TextFragment::new(text).font(if has_glyphs { init::GAME_FONT } else { init::FALLBACK_FONT })
+}
+
+/*
+ * Draws a line of text centered horizontally. y is given as a fraction of
+ * the free space, the way the title and game over texts were always laid out.
+ */
+pub fn draw_centered_text(renderer: &mut dyn Renderer, text: &str, scale: f32, divisor: f32, color: Color) {
+   let size = renderer.text_size(text, scale);
+   renderer.draw_text(text, scale, Vec2::new(
+      (PLAYFIELD_WIDTH - size.x) / 2.0,
+      (PLAYFIELD_HEIGHT - size.y) / divisor), color);
+}
+
+pub fn draw_playfield(renderer: &mut dyn Renderer, sim: &mut Simulation, alpha: f32) {
+   sim.state.draw_enemies(renderer, sim.count, alpha);
+   sim.state.draw_ufo(renderer, sim.count, alpha);
+   sim.state.draw_player(renderer, sim.count, alpha);
+}
+
+/*
+ * Lives, difficulty, score and the high score across the top.
+ */
+pub fn draw_hud(renderer: &mut dyn Renderer, sim: &Simulation, high_score: u64) {
+   // Blink the lives counter for a while after an extra life was awarded
+   let lives_color = if sim.extra_life_flash > 0 && (sim.extra_life_flash / 10).is_multiple_of(2) {
+      Color::from_rgb(0, 255, 0)
+   } else {
+      Color::WHITE
+   };
+
+   renderer.draw_text(&format!("LIVES  {}", sim.state.get_player_lives()), 40.0, Vec2::new(
+      PLAYFIELD_WIDTH / 20.0,
+      PLAYFIELD_HEIGHT / 50.0
+   ), lives_color);
+
+   renderer.draw_text(&sim.options.difficulty.name().to_uppercase(), 30.0, Vec2::new(
+      PLAYFIELD_WIDTH / 20.0,
+      PLAYFIELD_HEIGHT / 50.0 + 50.0
+   ), Color::WHITE);
+
+   if sim.extra_life_flash > 0 {
+      let size = renderer.text_size("EXTRA  LIFE", 60.0);
+
+      renderer.draw_text("EXTRA  LIFE", 60.0, Vec2::new(
+         (PLAYFIELD_WIDTH - size.x) / 2.0,
+         (PLAYFIELD_HEIGHT - size.y) / 2.0
+      ), lives_color);
+   }
+
+   renderer.draw_text(&format!("SCORE  {}", sim.score), 40.0, Vec2::new(
+      PLAYFIELD_WIDTH / 1.5,
+      PLAYFIELD_HEIGHT / 50.0
+   ), Color::WHITE);
+
+   let high_score = format!("HIGH  SCORE  {}", high_score.max(sim.score));
+   let size = renderer.text_size(&high_score, 40.0);
+
+   renderer.draw_text(&high_score, 40.0, Vec2::new(
+      PLAYFIELD_WIDTH - size.x * 1.5,
+      PLAYFIELD_HEIGHT / 50.0
+   ), Color::WHITE);
+}
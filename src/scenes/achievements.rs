@@ -0,0 +1,67 @@
+use ggez::Context;
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, Color};
+
+use crate::achievements::Achievement;
+use crate::game::Game;
+use crate::input::Action;
+use crate::render::{GpuRenderer, Renderer};
+use crate::scenes::{self, Scene, Transition};
+use crate::viewport::PLAYFIELD_WIDTH;
+
+const ROW_TOP: f32 = 290.0;
+const ROW_SPACING: f32 = 125.0;
+const NAME_SCALE: f32 = 50.0;
+const DESCRIPTION_SCALE: f32 = 32.0;
+// Names start this far left of the middle, the locked or unlocked mark
+// ends as far right of it
+const ROW_HALF_WIDTH: f32 = 560.0;
+
+/*
+ * Every achievement, unlocked ones in green, see achievements.rs.
+ */
+pub struct Achievements;
+
+impl Achievements {
+   pub fn new() -> Self {
+      Achievements
+   }
+}
+
+impl Scene for Achievements {
+   fn update(&mut self, game: &mut Game, _ctx: &mut Context, _dt: f32) -> Transition {
+      if game.input.take(Action::Confirm) || game.input.take(Action::Back) {
+         return Transition::Pop;
+      }
+
+      Transition::None
+   }
+
+   fn draw(&mut self, game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, _alpha: f32) {
+      scenes::draw_centered_text(canvas, ctx, "ACHIEVEMENTS", 120.0, 10.0, Color::WHITE);
+
+      let unlocked = Achievement::ALL.iter().filter(|achievement| game.achievements.is_unlocked(**achievement)).count();
+      let summary = format!("{}  OF  {}  UNLOCKED", unlocked, Achievement::ALL.len());
+      scenes::draw_centered_text(canvas, ctx, &summary, 50.0, 5.0, Color::WHITE);
+
+      let mut renderer = GpuRenderer::new(canvas, ctx);
+      for (index, achievement) in Achievement::ALL.iter().enumerate() {
+         let y = ROW_TOP + index as f32 * ROW_SPACING;
+         let left = PLAYFIELD_WIDTH / 2.0 - ROW_HALF_WIDTH;
+
+         let (mark, color) = if game.achievements.is_unlocked(*achievement) {
+            ("UNLOCKED", Color::from_rgb(0, 255, 0))
+         } else {
+            ("LOCKED", Color::from_rgb(120, 120, 120))
+         };
+         let mark_width = renderer.text_size(mark, NAME_SCALE).x;
+
+         renderer.draw_text(achievement.name(), NAME_SCALE, Vec2::new(left, y), color);
+         renderer.draw_text(mark, NAME_SCALE, Vec2::new(PLAYFIELD_WIDTH / 2.0 + ROW_HALF_WIDTH - mark_width, y), color);
+         renderer.draw_text(&achievement.description(), DESCRIPTION_SCALE, Vec2::new(left, y + NAME_SCALE + 8.0), Color::WHITE);
+      }
+
+      let prompt = format!("PRESS  {}  TO  CONTINUE", game.prompt(Action::Confirm));
+      scenes::draw_centered_text(canvas, ctx, &prompt, 60.0, 1.05, Color::WHITE);
+   }
+}
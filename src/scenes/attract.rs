@@ -0,0 +1,67 @@
+use ggez::Context;
+use ggez::graphics::{Canvas, Color};
+
+use crate::bot::Autopilot;
+use crate::game::Game;
+use crate::scenes::{self, Scene, Transition};
+use crate::simulation::TickOutcome;
+use crate::timestep::TICKS_PER_SECOND;
+
+// Back to the title after this long even if the bot is still alive
+const ATTRACT_MAX_TICKS: u32 = 90 * TICKS_PER_SECOND;
+
+/*
+ * The bot plays a silent game behind the title, like an arcade cabinet
+ * nobody is playing. Any key or button goes back to the title.
+ */
+pub struct Attract {
+   bot: Autopilot,
+   ticks: u32
+}
+
+impl Attract {
+   pub fn new() -> Self {
+      Attract {
+         bot: Autopilot::new(),
+         ticks: 0
+      }
+   }
+}
+
+impl Scene for Attract {
+   fn update(&mut self, game: &mut Game, _ctx: &mut Context, dt: f32) -> Transition {
+      self.ticks += 1;
+
+      let key = game.input.take_last_key();
+      let button = game.input.take_last_button();
+
+      if key.is_some() || button.is_some() {
+         // The press only ends the demo, it shouldn't also pick a menu item
+         game.input.clear_presses();
+         return Transition::Pop.faded();
+      }
+
+      let frame = self.bot.frame(&game.sim, dt);
+      let outcome = game.sim.tick(frame, dt);
+      game.sim.drain_events();
+
+      if outcome == TickOutcome::GameOver || self.ticks >= ATTRACT_MAX_TICKS {
+         return Transition::Pop.faded();
+      }
+
+      Transition::None
+   }
+
+   fn draw(&mut self, game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, alpha: f32) {
+      game.draw_playfield(canvas, ctx, alpha);
+      game.draw_hud(canvas, ctx);
+
+      scenes::draw_centered_text(canvas, ctx, "SPACE INVADERS", 150.0, 3.0, Color::WHITE);
+
+      if (self.ticks / 40).is_multiple_of(2) {
+         scenes::draw_centered_text(canvas, ctx, "DEMO", 80.0, 2.0, Color::WHITE);
+      }
+
+      scenes::draw_centered_text(canvas, ctx, "PRESS  ANY  KEY", 50.0, 1.6, Color::WHITE);
+   }
+}
@@ -0,0 +1,108 @@
+use ggez::Context;
+use ggez::graphics::{Canvas, Color};
+use ggez::input::keyboard::KeyCode;
+
+use crate::game::Game;
+use crate::gamepad::{self, GamepadMap};
+use crate::input::{self, Action, InputMap};
+use crate::menu::Menu;
+use crate::scenes::{self, Scene, Transition};
+use crate::viewport::PLAYFIELD_HEIGHT;
+
+const CONTROLS_RESET: usize = Action::ALL.len();
+const CONTROLS_BACK: usize = Action::ALL.len() + 1;
+
+/*
+ * Lists every action with its keys and gamepad buttons. Selecting an action
+ * waits for the next key or button press and binds it. Changes are saved to
+ * the settings file right away.
+ */
+pub struct Controls {
+   menu: Menu,
+   waiting_for: Option<Action>
+}
+
+impl Controls {
+   pub fn new() -> Self {
+      let mut items: Vec<&str> = Action::ALL.iter().map(|action| action.label()).collect();
+      items.push("RESET  TO  DEFAULTS");
+      items.push("BACK");
+
+      Controls {
+         menu: Menu::new(&items).with_layout(45.0, 70.0),
+         waiting_for: None
+      }
+   }
+}
+
+impl Scene for Controls {
+   fn update(&mut self, game: &mut Game, _ctx: &mut Context, _dt: f32) -> Transition {
+      if let Some(action) = self.waiting_for {
+         // Escape cancels instead of being bound
+         match game.input.take_last_key() {
+            Some(KeyCode::Escape) => self.waiting_for = None,
+            Some(key) => {
+               game.settings.controls.bind(action, key);
+               game.settings.save();
+               self.waiting_for = None;
+            },
+            None => {}
+         }
+
+         if let Some(button) = game.input.take_last_button() {
+            game.settings.gamepad.buttons.bind(action, button);
+            game.settings.save();
+            self.waiting_for = None;
+         }
+
+         return Transition::None;
+      }
+
+      self.menu.move_cursor(game.input.take_menu_movement());
+
+      if game.input.take(Action::Back) {
+         return Transition::Pop;
+      }
+
+      if game.input.take(Action::Confirm) {
+         match self.menu.selected() {
+            CONTROLS_RESET => {
+               game.settings.controls = InputMap::default();
+               game.settings.gamepad = GamepadMap::default();
+               game.settings.save();
+            },
+            CONTROLS_BACK => return Transition::Pop,
+            index => {
+               // Don't bind the key or button that was used to select the action
+               game.input.take_last_key();
+               game.input.take_last_button();
+               self.waiting_for = Action::ALL.get(index).copied();
+            }
+         }
+      }
+
+      Transition::None
+   }
+
+   fn draw(&mut self, game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, _alpha: f32) {
+      scenes::draw_centered_text(canvas, ctx, "CONTROLS", 120.0, 10.0, Color::WHITE);
+
+      if let Some(action) = self.waiting_for {
+         scenes::draw_centered_text(canvas, ctx, &format!("PRESS  A  KEY  OR  BUTTON  FOR  {}", action.label()), 70.0, 2.0, Color::WHITE);
+         scenes::draw_centered_text(canvas, ctx, "ESC  TO  CANCEL", 50.0, 1.6, Color::WHITE);
+         return;
+      }
+
+      for (index, action) in Action::ALL.iter().enumerate() {
+         let keys: Vec<String> = game.settings.controls.keys(*action).iter().map(|key| input::key_label(*key)).collect();
+         let keys = if keys.is_empty() { String::from("---") } else { keys.join("  ") };
+
+         let buttons: Vec<String> = game.settings.gamepad.buttons.buttons(*action).iter().map(|button| gamepad::button_label(*button)).collect();
+         let buttons = if buttons.is_empty() { String::from("---") } else { buttons.join("  ") };
+
+         self.menu.set_item(index, &format!("{}    {}    /    {}", action.label(), keys, buttons));
+      }
+
+      self.menu.draw(canvas, ctx, PLAYFIELD_HEIGHT / 5.0);
+   }
+}
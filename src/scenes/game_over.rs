@@ -0,0 +1,56 @@
+use ggez::Context;
+use ggez::graphics::{Canvas, Color};
+
+use crate::game::Game;
+use crate::input::Action;
+use crate::scenes::{self, NameEntry, Playing, Scene, Transition};
+
+pub struct GameOver {}
+
+impl GameOver {
+   pub fn new() -> Self {
+      GameOver {}
+   }
+}
+
+impl Scene for GameOver {
+   fn update(&mut self, game: &mut Game, _ctx: &mut Context, _dt: f32) -> Transition {
+      if game.input.take(Action::Back) {
+         return Transition::PopToRoot.faded();
+      }
+
+      if !game.input.take(Action::Confirm) {
+         return Transition::None;
+      }
+
+      if qualifies(game) {
+         Transition::Replace(Box::new(NameEntry::new()))
+      } else {
+         game.reset();
+         Transition::Replace(Box::new(Playing::new())).faded()
+      }
+   }
+
+   fn draw(&mut self, game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, _alpha: f32) {
+      scenes::draw_centered_text(canvas, ctx, "GAME OVER", 150.0, 3.0, Color::WHITE);
+      scenes::draw_centered_text(canvas, ctx, &format!("SCORE  {}", game.sim.score), 60.0, 2.0, Color::WHITE);
+
+      let confirm = game.prompt(Action::Confirm);
+
+      if game.sim.cheats.used() {
+         scenes::draw_centered_text(canvas, ctx, "CHEATS  USED  NO  HIGH  SCORE", 50.0, 1.8, Color::from_rgb(255, 80, 80));
+      }
+
+      if qualifies(game) {
+         scenes::draw_centered_text(canvas, ctx, "NEW  HIGH  SCORE", 60.0, 1.8, Color::from_rgb(0, 255, 0));
+         scenes::draw_centered_text(canvas, ctx, &format!("PRESS  {}  TO  ENTER  YOUR  NAME", confirm), 70.0, 1.5, Color::WHITE);
+      } else {
+         scenes::draw_centered_text(canvas, ctx, &format!("PRESS  {}  TO  RESTART", confirm), 70.0, 1.5, Color::WHITE);
+      }
+   }
+}
+
+// Games with cheats never make it onto the table
+fn qualifies(game: &Game) -> bool {
+   !game.sim.cheats.used() && game.high_scores.qualifies(game.sim.score, game.sim.options.difficulty)
+}
@@ -0,0 +1,74 @@
+use ggez::Context;
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, Color, DrawParam};
+
+use crate::difficulty::Difficulty;
+use crate::game::Game;
+use crate::input::Action;
+use crate::render;
+use crate::scenes::{self, Scene, Transition};
+use crate::viewport::PLAYFIELD_WIDTH;
+
+const ROW_TOP: f32 = 300.0;
+const ROW_SPACING: f32 = 60.0;
+
+/*
+ * One table per difficulty, left and right switch between them.
+ */
+pub struct HighScores {
+   difficulty: Difficulty,
+   // The entry that was just added to the difficulty's table, if coming
+   // from the name entry
+   highlight: Option<usize>
+}
+
+impl HighScores {
+   pub fn new(difficulty: Difficulty, highlight: Option<usize>) -> Self {
+      HighScores {
+         difficulty,
+         highlight
+      }
+   }
+}
+
+impl Scene for HighScores {
+   fn update(&mut self, game: &mut Game, _ctx: &mut Context, _dt: f32) -> Transition {
+      if game.input.take(Action::Confirm) || game.input.take(Action::Back) {
+         return Transition::Pop;
+      }
+
+      let direction = game.input.take_menu_horizontal();
+      if direction != 0 {
+         self.difficulty = self.difficulty.cycle(direction);
+         self.highlight = None;
+      }
+
+      Transition::None
+   }
+
+   fn draw(&mut self, game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, _alpha: f32) {
+      scenes::draw_centered_text(canvas, ctx, "HIGH  SCORES", 120.0, 10.0, Color::WHITE);
+
+      let difficulty = format!("{}  {}  {}", game.prompt(Action::MoveLeft), self.difficulty.name().to_uppercase(), game.prompt(Action::MoveRight));
+      scenes::draw_centered_text(canvas, ctx, &difficulty, 50.0, 5.0, Color::WHITE);
+
+      let entries = game.high_scores.entries(self.difficulty);
+      if entries.is_empty() {
+         scenes::draw_centered_text(canvas, ctx, "NO  SCORES  YET", 60.0, 2.0, Color::WHITE);
+      }
+
+      for (index, entry) in entries.iter().enumerate() {
+         let color = if Some(index) == self.highlight { Color::from_rgb(0, 255, 0) } else { Color::WHITE };
+
+         let text = render::text_in_font(&format!("{:>2}.  {:<3}  {:>8}", index + 1, entry.name, entry.score), 50.0);
+
+         canvas.draw(&text, DrawParam::default().dest(Vec2::new(
+            PLAYFIELD_WIDTH / 2.0 - 300.0,
+            ROW_TOP + index as f32 * ROW_SPACING
+         )).color(color));
+      }
+
+      let prompt = format!("PRESS  {}  TO  CONTINUE", game.prompt(Action::Confirm));
+      scenes::draw_centered_text(canvas, ctx, &prompt, 60.0, 1.05, Color::WHITE);
+   }
+}
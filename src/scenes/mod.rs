@@ -0,0 +1,207 @@
+mod achievements;
+mod attract;
+mod controls;
+mod game_over;
+mod high_scores;
+mod name_entry;
+mod paused;
+mod playing;
+mod settings;
+mod statistics;
+mod title;
+mod wave_intro;
+
+pub use achievements::Achievements;
+pub use attract::Attract;
+pub use controls::Controls;
+pub use game_over::GameOver;
+pub use high_scores::HighScores;
+pub use name_entry::NameEntry;
+pub use paused::Paused;
+pub use playing::Playing;
+pub use settings::Settings;
+pub use statistics::Statistics;
+pub use title::Title;
+pub use wave_intro::WaveIntro;
+
+use ggez::Context;
+use ggez::graphics::{self, Canvas, Color, DrawParam, Rect};
+
+use crate::game::Game;
+use crate::render::{self, GpuRenderer};
+use crate::viewport::{PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT};
+
+// Length of each half (out and in) of a fade between scenes, in ticks.
+const FADE_TICKS: u32 = 20;
+
+/*
+ * What the scene stack should do after a scene's update.
+ */
+pub enum Transition {
+   None,
+   Push(Box<dyn Scene>),
+   Pop,
+   Replace(Box<dyn Scene>),
+   // Pop everything but the bottom scene (the title screen)
+   PopToRoot,
+   Quit,
+   // Fade the screen out, do the transition and fade back in
+   Fade(Box<Transition>)
+}
+
+impl Transition {
+   pub fn faded(self) -> Transition {
+      Transition::Fade(Box::new(self))
+   }
+}
+
+/*
+ * A screen of the game. Only the scene on top of the stack is updated and
+ * gets to read input. Overlay scenes are drawn on top of the scene below them.
+ */
+pub trait Scene {
+   fn update(&mut self, game: &mut Game, ctx: &mut Context, dt: f32) -> Transition;
+
+   /*
+    * alpha is how far between two simulation ticks the frame is drawn. It is
+    * always 1.0 for scenes that aren't on top, since they are frozen.
+    */
+   fn draw(&mut self, game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, alpha: f32);
+
+   fn is_overlay(&self) -> bool {
+      false
+   }
+
+   fn focus_lost(&mut self, _game: &mut Game) -> Transition {
+      Transition::None
+   }
+}
+
+struct Fade {
+   transition: Option<Transition>,
+   tick: u32
+}
+
+impl Fade {
+   // 0 is fully visible, 1 is black
+   fn opacity(&self) -> f32 {
+      if self.tick < FADE_TICKS {
+         self.tick as f32 / FADE_TICKS as f32
+      } else {
+         1.0 - (self.tick - FADE_TICKS) as f32 / FADE_TICKS as f32
+      }
+   }
+}
+
+pub struct SceneStack {
+   scenes: Vec<Box<dyn Scene>>,
+   fade: Option<Fade>
+}
+
+impl SceneStack {
+   pub fn new(root: Box<dyn Scene>) -> Self {
+      SceneStack {
+         scenes: vec![root],
+         fade: None
+      }
+   }
+
+   pub fn push(&mut self, scene: Box<dyn Scene>) {
+      self.scenes.push(scene);
+   }
+
+   /*
+    * Updates the top scene and applies the transition it asks for. Returns
+    * false once the game should quit.
+    */
+   pub fn update(&mut self, game: &mut Game, ctx: &mut Context, dt: f32) -> bool {
+      if let Some(fade) = self.fade.as_mut() {
+         fade.tick += 1;
+
+         if fade.tick == FADE_TICKS {
+            if let Some(transition) = fade.transition.take() {
+               if !self.apply(transition) {
+                  return false;
+               }
+            }
+         } else if fade.tick >= 2 * FADE_TICKS {
+            self.fade = None;
+         }
+
+         return true;
+      }
+
+      let transition = match self.scenes.last_mut() {
+         Some(scene) => scene.update(game, ctx, dt),
+         None => return false
+      };
+
+      self.apply(transition)
+   }
+
+   pub fn focus_lost(&mut self, game: &mut Game) -> bool {
+      if self.fade.is_some() {
+         return true;
+      }
+
+      let transition = match self.scenes.last_mut() {
+         Some(scene) => scene.focus_lost(game),
+         None => return false
+      };
+
+      self.apply(transition)
+   }
+
+   fn apply(&mut self, transition: Transition) -> bool {
+      match transition {
+         Transition::None => {},
+         Transition::Push(scene) => self.scenes.push(scene),
+         Transition::Pop => {
+            self.scenes.pop();
+         },
+         Transition::Replace(scene) => {
+            self.scenes.pop();
+            self.scenes.push(scene);
+         },
+         Transition::PopToRoot => self.scenes.truncate(1),
+         Transition::Quit => return false,
+         Transition::Fade(transition) => {
+            self.fade = Some(Fade { transition: Some(*transition), tick: 0 });
+         }
+      }
+
+      !self.scenes.is_empty()
+   }
+
+   pub fn draw(&mut self, game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, alpha: f32) {
+      // Start from the topmost scene that isn't an overlay
+      let first_visible = self.scenes.iter().rposition(|scene| !scene.is_overlay()).unwrap_or(0);
+      let top = self.scenes.len().saturating_sub(1);
+      let frozen = self.fade.is_some();
+
+      for (index, scene) in self.scenes.iter_mut().enumerate().skip(first_visible) {
+         let scene_alpha = if index == top && !frozen { alpha } else { 1.0 };
+         scene.draw(game, canvas, ctx, scene_alpha);
+      }
+
+      if let Some(fade) = self.fade.as_ref() {
+         dim(canvas, fade.opacity());
+      }
+   }
+}
+
+/*
+ * Draws a translucent black layer over the whole playfield.
+ */
+pub fn dim(canvas: &mut Canvas, opacity: f32) {
+   canvas.draw(&graphics::Quad, DrawParam::default()
+               .dest_rect(Rect::new(0.0, 0.0, PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT))
+               .color(Color::new(0.0, 0.0, 0.0, opacity)));
+}
+
+/*
+ * Draws a line of text centered horizontally, see render::draw_centered_text.
+ */
+pub fn draw_centered_text(canvas: &mut Canvas, ctx: &mut Context, text: &str, scale: f32, divisor: f32, color: Color) {
+   render::draw_centered_text(&mut GpuRenderer::new(canvas, ctx), text, scale, divisor, color);
+}
@@ -0,0 +1,86 @@
+use ggez::Context;
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, Color, DrawParam};
+
+use crate::game::Game;
+use crate::input::Action;
+use crate::highscores::NAME_LENGTH;
+use crate::render;
+use crate::scenes::{self, HighScores, Scene, Transition};
+use crate::viewport::{PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT};
+
+const LETTER_SPACING: f32 = 120.0;
+
+/*
+ * Arcade style initials entry. Up and down change the letter under the
+ * cursor, left and right move the cursor, confirm moves on to the next
+ * letter and saves the entry after the last one.
+ */
+pub struct NameEntry {
+   letters: [u8; NAME_LENGTH],
+   cursor: usize
+}
+
+impl NameEntry {
+   pub fn new() -> Self {
+      NameEntry {
+         letters: [b'A'; NAME_LENGTH],
+         cursor: 0
+      }
+   }
+
+   fn name(&self) -> String {
+      self.letters.iter().map(|letter| *letter as char).collect()
+   }
+}
+
+impl Scene for NameEntry {
+   fn update(&mut self, game: &mut Game, _ctx: &mut Context, _dt: f32) -> Transition {
+      let letter = &mut self.letters[self.cursor];
+      match game.input.take_menu_movement() {
+         -1 => *letter = if *letter == b'Z' { b'A' } else { *letter + 1 },
+         1 => *letter = if *letter == b'A' { b'Z' } else { *letter - 1 },
+         _ => {}
+      }
+
+      match game.input.take_menu_horizontal() {
+         -1 => self.cursor = self.cursor.saturating_sub(1),
+         1 => self.cursor = (self.cursor + 1).min(NAME_LENGTH - 1),
+         _ => {}
+      }
+
+      if game.input.take(Action::Confirm) {
+         if self.cursor + 1 < NAME_LENGTH {
+            self.cursor += 1;
+         } else {
+            let position = game.high_scores.insert(&self.name(), game.sim.score, game.sim.options.difficulty);
+            return Transition::Replace(Box::new(HighScores::new(game.sim.options.difficulty, position)));
+         }
+      }
+
+      Transition::None
+   }
+
+   fn draw(&mut self, game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, _alpha: f32) {
+      scenes::draw_centered_text(canvas, ctx, "NEW  HIGH  SCORE", 120.0, 5.0, Color::WHITE);
+      scenes::draw_centered_text(canvas, ctx, &format!("SCORE  {}", game.sim.score), 60.0, 3.0, Color::WHITE);
+
+      let left = (PLAYFIELD_WIDTH - LETTER_SPACING * NAME_LENGTH as f32) / 2.0;
+      for (index, letter) in self.letters.iter().enumerate() {
+         let color = if index == self.cursor { Color::from_rgb(0, 255, 0) } else { Color::WHITE };
+
+         let text = render::text_in_font(&(*letter as char).to_string(), 120.0);
+
+         canvas.draw(&text, DrawParam::default().dest(Vec2::new(
+            left + index as f32 * LETTER_SPACING,
+            PLAYFIELD_HEIGHT / 2.0
+         )).color(color));
+      }
+
+      let change_prompt = format!("{}  {}  TO  CHANGE  LETTER", game.prompt(Action::MenuUp), game.prompt(Action::MenuDown));
+      let confirm_prompt = format!("{}  TO  CONFIRM", game.prompt(Action::Confirm));
+
+      scenes::draw_centered_text(canvas, ctx, &change_prompt, 50.0, 1.4, Color::WHITE);
+      scenes::draw_centered_text(canvas, ctx, &confirm_prompt, 50.0, 1.25, Color::WHITE);
+   }
+}
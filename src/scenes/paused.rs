@@ -0,0 +1,65 @@
+use ggez::Context;
+use ggez::graphics::{Canvas, Color};
+
+use crate::game::Game;
+use crate::input::Action;
+use crate::menu::Menu;
+use crate::scenes::{self, Scene, Settings, Transition};
+use crate::viewport::PLAYFIELD_HEIGHT;
+
+const PAUSE_RESUME: usize = 0;
+const PAUSE_RESTART: usize = 1;
+const PAUSE_SETTINGS: usize = 2;
+const PAUSE_QUIT_TO_TITLE: usize = 3;
+
+pub struct Paused {
+   menu: Menu
+}
+
+impl Paused {
+   pub fn new() -> Self {
+      Paused {
+         menu: Menu::new(&["RESUME", "RESTART", "SETTINGS", "QUIT  TO  TITLE"])
+      }
+   }
+}
+
+impl Scene for Paused {
+   fn update(&mut self, game: &mut Game, _ctx: &mut Context, _dt: f32) -> Transition {
+      self.menu.move_cursor(game.input.take_menu_movement());
+
+      if game.input.take(Action::Pause) {
+         return Transition::Pop;
+      }
+
+      if !game.input.take(Action::Confirm) {
+         return Transition::None;
+      }
+
+      match self.menu.selected() {
+         PAUSE_RESUME => Transition::Pop,
+         PAUSE_RESTART => {
+            game.quit();
+            game.reset();
+            Transition::Pop
+         },
+         PAUSE_SETTINGS => Transition::Push(Box::new(Settings::new())),
+         PAUSE_QUIT_TO_TITLE => {
+            game.quit();
+            Transition::PopToRoot.faded()
+         },
+         _ => Transition::None
+      }
+   }
+
+   fn draw(&mut self, _game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, _alpha: f32) {
+      scenes::dim(canvas, 0.7);
+      scenes::draw_centered_text(canvas, ctx, "PAUSED", 150.0, 5.0, Color::WHITE);
+
+      self.menu.draw(canvas, ctx, PLAYFIELD_HEIGHT / 2.0);
+   }
+
+   fn is_overlay(&self) -> bool {
+      true
+   }
+}
@@ -0,0 +1,77 @@
+use ggez::Context;
+use ggez::graphics::Canvas;
+
+use crate::game::Game;
+use crate::input::Action;
+use crate::input_source::LiveSource;
+use crate::scenes::{GameOver, Paused, Scene, Transition, WaveIntro};
+use crate::simulation::TickOutcome;
+
+/*
+ * The game itself. Runs one simulation tick per update.
+ */
+pub struct Playing {
+   started: bool
+}
+
+impl Playing {
+   pub fn new() -> Self {
+      Playing {
+         started: false
+      }
+   }
+
+   fn pause(&mut self, game: &mut Game) -> Transition {
+      game.audio.set_paused(true);
+      Transition::Push(Box::new(Paused::new()))
+   }
+}
+
+impl Scene for Playing {
+   fn update(&mut self, game: &mut Game, _ctx: &mut Context, dt: f32) -> Transition {
+      if !self.started {
+         self.started = true;
+         return Transition::Push(Box::new(WaveIntro::new(game.sim.wave)));
+      }
+
+      if game.input.take(Action::Pause) {
+         return self.pause(game);
+      }
+
+      game.audio.set_paused(false);
+
+      let live = game.input.frame(&game.settings.controls, &game.settings.gamepad);
+      let frame = match game.input_source.next_frame(live) {
+         Some(frame) => frame,
+         None => {
+            // The replay or remote player is done, hand control back to the local player
+            game.input_source = Box::new(LiveSource);
+            live
+         }
+      };
+
+      let outcome = game.sim.tick(frame, dt);
+      game.dispatch_events();
+
+      let march_interval = game.sim.state.march_interval();
+      game.audio.update_march(dt, march_interval);
+
+      match outcome {
+         TickOutcome::Running => Transition::None,
+         TickOutcome::WaveCleared => {
+            game.audio.reset_march();
+            Transition::Push(Box::new(WaveIntro::new(game.sim.wave)))
+         },
+         TickOutcome::GameOver => Transition::Replace(Box::new(GameOver::new())).faded()
+      }
+   }
+
+   fn draw(&mut self, game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, alpha: f32) {
+      game.draw_playfield(canvas, ctx, alpha);
+      game.draw_hud(canvas, ctx);
+   }
+
+   fn focus_lost(&mut self, game: &mut Game) -> Transition {
+      self.pause(game)
+   }
+}
@@ -0,0 +1,83 @@
+use ggez::Context;
+use ggez::graphics::{Canvas, Color};
+
+use crate::audio;
+use crate::game::Game;
+use crate::input::Action;
+use crate::menu::Menu;
+use crate::scenes::{self, Controls, Scene, Transition};
+use crate::viewport::PLAYFIELD_HEIGHT;
+
+const SETTINGS_FULLSCREEN: usize = 0;
+const SETTINGS_MASTER_VOLUME: usize = 1;
+const SETTINGS_SFX_VOLUME: usize = 2;
+const SETTINGS_MUSIC_VOLUME: usize = 3;
+const SETTINGS_MUTE: usize = 4;
+const SETTINGS_CONTROLS: usize = 5;
+const SETTINGS_BACK: usize = 6;
+
+/*
+ * Volumes are changed with left and right while they are selected.
+ */
+pub struct Settings {
+   menu: Menu
+}
+
+impl Settings {
+   pub fn new() -> Self {
+      Settings {
+         menu: Menu::new(&["FULLSCREEN  OFF", "MASTER  VOLUME", "SFX  VOLUME", "MUSIC  VOLUME", "SOUND  ON", "CONTROLS", "BACK"])
+                    .with_layout(50.0, 75.0)
+      }
+   }
+}
+
+impl Scene for Settings {
+   fn update(&mut self, game: &mut Game, ctx: &mut Context, _dt: f32) -> Transition {
+      self.menu.move_cursor(game.input.take_menu_movement());
+
+      if game.input.take(Action::Back) {
+         return Transition::Pop;
+      }
+
+      let direction = game.input.take_menu_horizontal();
+      if direction != 0 {
+         match self.menu.selected() {
+            SETTINGS_MASTER_VOLUME => game.update_audio_settings(|audio| audio.master_volume = audio::step_volume(audio.master_volume, direction)),
+            SETTINGS_SFX_VOLUME => game.update_audio_settings(|audio| audio.sfx_volume = audio::step_volume(audio.sfx_volume, direction)),
+            SETTINGS_MUSIC_VOLUME => game.update_audio_settings(|audio| audio.music_volume = audio::step_volume(audio.music_volume, direction)),
+            _ => {}
+         }
+      }
+
+      if game.input.take(Action::Confirm) {
+         match self.menu.selected() {
+            SETTINGS_FULLSCREEN => game.toggle_fullscreen(ctx),
+            SETTINGS_MUTE => game.update_audio_settings(|audio| audio.muted = !audio.muted),
+            SETTINGS_CONTROLS => return Transition::Push(Box::new(Controls::new())),
+            SETTINGS_BACK => return Transition::Pop,
+            _ => {}
+         }
+      }
+
+      Transition::None
+   }
+
+   fn draw(&mut self, game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, _alpha: f32) {
+      scenes::draw_centered_text(canvas, ctx, "SETTINGS", 150.0, 5.0, Color::WHITE);
+
+      let fullscreen = if game.fullscreen { "FULLSCREEN  ON" } else { "FULLSCREEN  OFF" };
+      let audio = &game.settings.audio;
+      let mute = if audio.muted { "SOUND  OFF" } else { "SOUND  ON" };
+
+      self.menu.set_item(SETTINGS_FULLSCREEN, fullscreen);
+      self.menu.set_item(SETTINGS_MASTER_VOLUME, &format!("MASTER  VOLUME   {}", audio.master_volume));
+      self.menu.set_item(SETTINGS_SFX_VOLUME, &format!("SFX  VOLUME   {}", audio.sfx_volume));
+      self.menu.set_item(SETTINGS_MUSIC_VOLUME, &format!("MUSIC  VOLUME   {}", audio.music_volume));
+      self.menu.set_item(SETTINGS_MUTE, mute);
+      self.menu.draw(canvas, ctx, PLAYFIELD_HEIGHT / 3.0);
+
+      let hint = format!("{}  {}  TO  CHANGE  VOLUME   {}  TO  MUTE  ANYWHERE", game.prompt(Action::MoveLeft), game.prompt(Action::MoveRight), game.prompt(Action::Mute));
+      scenes::draw_centered_text(canvas, ctx, &hint, 35.0, 1.05, Color::WHITE);
+   }
+}
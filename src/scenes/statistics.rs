@@ -0,0 +1,74 @@
+use ggez::Context;
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, Color};
+
+use crate::game::Game;
+use crate::input::Action;
+use crate::render::{GpuRenderer, Renderer};
+use crate::scenes::{self, Scene, Transition};
+use crate::statistics::UserStatistics;
+use crate::viewport::PLAYFIELD_WIDTH;
+
+const ROW_TOP: f32 = 250.0;
+const ROW_SPACING: f32 = 62.0;
+const ROW_SCALE: f32 = 45.0;
+// Labels start this far left of the middle, values end as far right of it
+const ROW_HALF_WIDTH: f32 = 520.0;
+
+/*
+ * The totals over every game played so far, see statistics.rs.
+ */
+pub struct Statistics;
+
+impl Statistics {
+   pub fn new() -> Self {
+      Statistics
+   }
+}
+
+impl Scene for Statistics {
+   fn update(&mut self, game: &mut Game, _ctx: &mut Context, _dt: f32) -> Transition {
+      if game.input.take(Action::Confirm) || game.input.take(Action::Back) {
+         return Transition::Pop;
+      }
+
+      Transition::None
+   }
+
+   fn draw(&mut self, game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, _alpha: f32) {
+      scenes::draw_centered_text(canvas, ctx, "STATISTICS", 120.0, 10.0, Color::WHITE);
+
+      let mut renderer = GpuRenderer::new(canvas, ctx);
+      for (index, (label, value)) in rows(&game.stats).iter().enumerate() {
+         let y = ROW_TOP + index as f32 * ROW_SPACING;
+         let width = renderer.text_size(value, ROW_SCALE).x;
+
+         renderer.draw_text(label, ROW_SCALE, Vec2::new(PLAYFIELD_WIDTH / 2.0 - ROW_HALF_WIDTH, y), Color::WHITE);
+         renderer.draw_text(value, ROW_SCALE, Vec2::new(PLAYFIELD_WIDTH / 2.0 + ROW_HALF_WIDTH - width, y), Color::from_rgb(0, 255, 0));
+      }
+
+      let prompt = format!("PRESS  {}  TO  CONTINUE", game.prompt(Action::Confirm));
+      scenes::draw_centered_text(canvas, ctx, &prompt, 60.0, 1.05, Color::WHITE);
+   }
+}
+
+fn rows(stats: &UserStatistics) -> Vec<(&'static str, String)> {
+   vec![
+      ("GAMES  PLAYED", stats.games_played.to_string()),
+      ("PLAY  TIME", play_time(stats.play_seconds())),
+      ("SHOTS  FIRED", stats.shots_fired.to_string()),
+      ("ACCURACY", format!("{}  PERCENT", stats.accuracy())),
+      ("INVADERS  A  KILLED", stats.invaders_a_killed.to_string()),
+      ("INVADERS  B  KILLED", stats.invaders_b_killed.to_string()),
+      ("UFOS  DESTROYED", stats.ufos_destroyed.to_string()),
+      ("HIGHEST  WAVE", stats.highest_wave.to_string()),
+      ("LIVES  LOST  TO  INVADER  FIRE", stats.lives_lost.to_string()),
+      ("GAMES  LOST", stats.games_lost.to_string()),
+      ("GAMES  QUIT", stats.games_quit.to_string())
+   ]
+}
+
+// The font has no colons
+fn play_time(seconds: u64) -> String {
+   format!("{}H  {:02}M  {:02}S", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
@@ -0,0 +1,108 @@
+use ggez::Context;
+use ggez::graphics::{Canvas, Color};
+
+use crate::difficulty::Difficulty;
+use crate::game::Game;
+use crate::input::Action;
+use crate::menu::Menu;
+use crate::render::{self, GpuRenderer, Renderer};
+use crate::scenes::{Achievements, Attract, HighScores, Playing, Scene, Settings, Statistics, Transition};
+use crate::timestep::TICKS_PER_SECOND;
+use crate::viewport::PLAYFIELD_HEIGHT;
+
+const TITLE_START: usize = 0;
+const TITLE_DIFFICULTY: usize = 1;
+const TITLE_HIGH_SCORES: usize = 2;
+const TITLE_STATISTICS: usize = 3;
+const TITLE_ACHIEVEMENTS: usize = 4;
+const TITLE_SETTINGS: usize = 5;
+const TITLE_QUIT: usize = 6;
+
+// The demo starts after this long without any input
+const ATTRACT_AFTER_TICKS: u32 = 20 * TICKS_PER_SECOND;
+
+pub struct Title {
+   menu: Menu,
+   idle_ticks: u32
+}
+
+impl Title {
+   pub fn new() -> Self {
+      Title {
+         menu: Menu::new(&["START  GAME", "DIFFICULTY  NORMAL", "HIGH  SCORES", "STATISTICS", "ACHIEVEMENTS", "SETTINGS", "QUIT"])
+                    .with_layout(45.0, 57.0),
+         idle_ticks: 0
+      }
+   }
+
+   /*
+    * Draws the title screen with the controls named by prompt, so it can be
+    * drawn without a running game (see snapshots.rs).
+    */
+   pub fn render(&mut self, renderer: &mut dyn Renderer, prompt: impl Fn(Action) -> String, difficulty: Difficulty) {
+      let move_prompt = format!("Move  with  {}  and  {}", prompt(Action::MoveLeft), prompt(Action::MoveRight));
+      let shoot_prompt = format!("Shoot  with  {}", prompt(Action::Fire));
+
+      render::draw_centered_text(renderer, "SPACE INVADERS", 150.0, 3.0, Color::WHITE);
+      render::draw_centered_text(renderer, &move_prompt, 60.0, 2.0, Color::WHITE);
+      render::draw_centered_text(renderer, &shoot_prompt, 60.0, 1.8, Color::WHITE);
+
+      let difficulty = format!("DIFFICULTY  {}", difficulty.name().to_uppercase());
+      self.menu.set_item(TITLE_DIFFICULTY, &difficulty);
+      self.menu.render(renderer, PLAYFIELD_HEIGHT / 1.6);
+   }
+}
+
+impl Scene for Title {
+   fn update(&mut self, game: &mut Game, _ctx: &mut Context, _dt: f32) -> Transition {
+      if game.input.is_active() {
+         self.idle_ticks = 0;
+      } else {
+         self.idle_ticks += 1;
+      }
+
+      if self.idle_ticks >= ATTRACT_AFTER_TICKS {
+         self.idle_ticks = 0;
+         game.reset_demo();
+         return Transition::Push(Box::new(Attract::new())).faded();
+      }
+
+      self.menu.move_cursor(game.input.take_menu_movement());
+
+      if game.input.take(Action::Back) {
+         return Transition::Quit;
+      }
+
+      // The difficulty is changed with left and right, or by selecting it
+      let direction = game.input.take_menu_horizontal();
+      if direction != 0 && self.menu.selected() == TITLE_DIFFICULTY {
+         game.set_difficulty(game.sim.options.difficulty.cycle(direction));
+      }
+
+      if !game.input.take(Action::Confirm) {
+         return Transition::None;
+      }
+
+      match self.menu.selected() {
+         TITLE_START => {
+            game.reset();
+            Transition::Push(Box::new(Playing::new())).faded()
+         },
+         TITLE_DIFFICULTY => {
+            game.set_difficulty(game.sim.options.difficulty.cycle(1));
+            Transition::None
+         },
+         TITLE_HIGH_SCORES => Transition::Push(Box::new(HighScores::new(game.sim.options.difficulty, None))),
+         TITLE_STATISTICS => Transition::Push(Box::new(Statistics::new())),
+         TITLE_ACHIEVEMENTS => Transition::Push(Box::new(Achievements::new())),
+         TITLE_SETTINGS => Transition::Push(Box::new(Settings::new())),
+         TITLE_QUIT => Transition::Quit,
+         _ => Transition::None
+      }
+   }
+
+   fn draw(&mut self, game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, _alpha: f32) {
+      let difficulty = game.sim.options.difficulty;
+      self.render(&mut GpuRenderer::new(canvas, ctx), |action| game.prompt(action), difficulty);
+   }
+}
@@ -0,0 +1,45 @@
+use ggez::Context;
+use ggez::graphics::{Canvas, Color};
+
+use crate::game::Game;
+use crate::input::Action;
+use crate::scenes::{self, Scene, Transition};
+
+const WAVE_INTRO_TICKS: u32 = 90;
+
+/*
+ * Shows the wave number over the frozen playfield before a wave starts.
+ */
+pub struct WaveIntro {
+   wave: u32,
+   ticks: u32
+}
+
+impl WaveIntro {
+   pub fn new(wave: u32) -> Self {
+      WaveIntro {
+         wave,
+         ticks: 0
+      }
+   }
+}
+
+impl Scene for WaveIntro {
+   fn update(&mut self, game: &mut Game, _ctx: &mut Context, _dt: f32) -> Transition {
+      self.ticks += 1;
+
+      if self.ticks >= WAVE_INTRO_TICKS || game.input.take(Action::Confirm) {
+         return Transition::Pop;
+      }
+
+      Transition::None
+   }
+
+   fn draw(&mut self, _game: &mut Game, canvas: &mut Canvas, ctx: &mut Context, _alpha: f32) {
+      scenes::draw_centered_text(canvas, ctx, &format!("WAVE  {}", self.wave), 120.0, 2.0, Color::WHITE);
+   }
+
+   fn is_overlay(&self) -> bool {
+      true
+   }
+}
@@ -0,0 +1,56 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::AudioSettings;
+use crate::gamepad::GamepadMap;
+use crate::input::InputMap;
+
+/*
+ * Per user preferences, kept in a TOML file next to the high scores.
+ * Anything missing from the file falls back to its default.
+ */
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+   pub controls: InputMap,
+   pub gamepad: GamepadMap,
+   pub audio: AudioSettings,
+   #[serde(skip)]
+   path: PathBuf
+}
+
+impl UserSettings {
+   pub fn load(path: &Path) -> Self {
+      let mut settings = if path.exists() {
+         match fs::read_to_string(path).map_err(|err| err.to_string())
+                                       .and_then(|contents| toml::from_str::<UserSettings>(&contents).map_err(|err| err.to_string())) {
+            Err(err) => {
+               eprintln!("Failed to load settings from {}, using defaults: {}", path.display(), err);
+               UserSettings::default()
+            },
+            Ok(settings) => settings
+         }
+      } else {
+         UserSettings::default()
+      };
+
+      settings.path = PathBuf::from(path);
+      settings
+   }
+
+   pub fn save(&self) {
+      let contents = match toml::to_string_pretty(self) {
+         Err(err) => {
+            eprintln!("Failed to serialize settings: {}", err);
+            return;
+         },
+         Ok(contents) => contents
+      };
+
+      if let Err(err) = fs::write(&self.path, contents) {
+         eprintln!("Failed to write settings to {}: {}", self.path.display(), err);
+      }
+   }
+}
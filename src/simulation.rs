@@ -0,0 +1,298 @@
+use ggez::glam::Vec2;
+
+use crate::config::GameConfig;
+use crate::difficulty::Difficulty;
+use crate::entities::Enemy;
+use crate::events::{GameEvent, GameOverReason};
+use crate::init;
+use crate::input_source::InputFrame;
+use crate::lives::ExtraLifeTracker;
+use crate::sprites::SpriteManifest;
+use crate::state::State;
+use crate::viewport::{PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT};
+
+pub const EXTRA_LIFE_FLASH_FRAMES: u64 = 120;
+
+/*
+ * How a game is started, fixed for the whole session.
+ */
+#[derive(Clone, Debug)]
+pub struct GameOptions {
+   pub difficulty: Difficulty,
+   pub start_wave: u32
+}
+
+impl Default for GameOptions {
+   fn default() -> Self {
+      GameOptions {
+         difficulty: Difficulty::Normal,
+         start_wave: 1
+      }
+   }
+}
+
+/*
+ * Cheats for the current game, set from the developer console (see
+ * console.rs). A game where any were used doesn't get a high score.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Cheats {
+   // Enemy bullets go through the player
+   pub god_mode: bool,
+   // Firing again brings the bullet in the air back to the player
+   pub rapid_fire: bool,
+   // Every console command run during the game
+   pub log: Vec<String>
+}
+
+impl Cheats {
+   pub fn used(&self) -> bool {
+      !self.log.is_empty()
+   }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TickOutcome {
+   Running,
+   WaveCleared,
+   GameOver
+}
+
+/*
+ * One game of Space Invaders without anything to do with windows, sound or
+ * menus: the playfield, the score and the wave. Everything that plays the
+ * game (the window, the headless runner) drives it one tick at a time.
+ */
+pub struct Simulation {
+   pub state: Box<State>,
+   pub count: u64,
+   pub score: u64,
+   pub wave: u32,
+   pub extra_lives: ExtraLifeTracker,
+   pub extra_life_flash: u64,
+   pub options: GameOptions,
+   pub cheats: Cheats,
+   // Events of the simulation itself, the state's come on top
+   events: Vec<GameEvent>,
+   // With the difficulty applied
+   config: GameConfig,
+   sprites: SpriteManifest,
+   image_dimensions: Vec2
+}
+
+impl Simulation {
+   /*
+    * image_dimensions is the size of the sprite sheet as (height, width),
+    * the way the entities expect it. The difficulty is applied on top of
+    * config.
+    */
+   pub fn new(image_dimensions: Vec2, config: GameConfig, sprites: SpriteManifest, options: GameOptions) -> Self {
+      let mut config = config;
+      options.difficulty.apply(&mut config);
+
+      let mut sim = Simulation {
+         state: Box::new(new_state(&config, &sprites, image_dimensions)),
+         count: 0,
+         score: 0,
+         wave: options.start_wave,
+         extra_lives: ExtraLifeTracker::new(config.extra_life_config()),
+         extra_life_flash: 0,
+         options,
+         cheats: Cheats::default(),
+         events: Vec::new(),
+         config,
+         sprites,
+         image_dimensions
+      };
+
+      sim.state.configure_player_lives(sim.config.player.lives, sim.config.player.max_lives);
+      sim
+   }
+
+   pub fn image_dimensions(&self) -> Vec2 {
+      self.image_dimensions
+   }
+
+   /*
+    * Puts everything back to how it is at the start of a game. The same seed
+    * always plays out the same way for the same input.
+    */
+   pub fn reset(&mut self, seed: u64) {
+      *self.state = new_state(&self.config, &self.sprites, self.image_dimensions);
+      self.state.configure_player_lives(self.config.player.lives, self.config.player.max_lives);
+      self.state.reseed(seed);
+      self.state.reset_ufo();
+
+      self.state.reset_enemy_movement_speed(self.config.enemies.starting_speed);
+      self.state.set_enemy_direction_right();
+
+      // Later starting waves start as fast as they would have gotten
+      let start_wave = self.options.start_wave.max(1);
+      let speed_up = self.speed_up(start_wave);
+      self.state.increase_enemy_movement_speed(speed_up);
+
+      self.count = 0;
+      self.score = 0;
+      self.wave = start_wave;
+      self.extra_lives.reset();
+      self.extra_life_flash = 0;
+      self.cheats = Cheats::default();
+      self.events.clear();
+      self.events.push(GameEvent::GameStarted { wave: start_wave });
+   }
+
+   // How much faster than at the start of a game the invaders are on wave
+   fn speed_up(&self, wave: u32) -> u8 {
+      let speed_up = (wave.max(1) - 1).saturating_mul(self.config.enemies.speed_per_wave as u32);
+      speed_up.min(u8::MAX as u32) as u8
+   }
+
+   /*
+    * Skips to the start of another wave, with the speed the invaders would
+    * have gotten to by then.
+    */
+   #[cfg(feature = "dev")]
+   pub fn jump_to_wave(&mut self, wave: u32) {
+      let enemies = new_formation(&self.config, &self.sprites, self.image_dimensions);
+      self.state.set_formation(enemies, self.config.enemies.per_row);
+      self.state.reset_ufo();
+
+      let speed_up = self.speed_up(wave);
+      self.state.set_enemy_direction_right();
+      self.state.reset_enemy_movement_speed(self.config.enemies.starting_speed);
+      self.state.increase_enemy_movement_speed(speed_up);
+      self.wave = wave.max(1);
+   }
+
+   pub fn load_next_wave(&mut self) {
+      let enemies = new_formation(&self.config, &self.sprites, self.image_dimensions);
+      self.state.set_formation(enemies, self.config.enemies.per_row);
+      self.state.set_enemy_direction_right();
+      self.state.increase_enemy_movement_speed(self.config.enemies.speed_per_wave);
+      self.state.reset_ufo();
+      self.wave += 1;
+   }
+
+   /*
+    * Switches to another gameplay config in the middle of a game. Speeds,
+    * fire rates and scoring change right away, the size of the formation
+    * with the next wave and the lives and sizes of the player and the ufo
+    * with the next game.
+    */
+   pub fn set_config(&mut self, config: GameConfig) {
+      let mut config = config;
+      self.options.difficulty.apply(&mut config);
+
+      // Thresholds that were already passed aren't awarded again
+      self.extra_lives = ExtraLifeTracker::new(config.extra_life_config());
+      self.extra_lives.check(self.score);
+
+      self.state.set_config(config.clone());
+      self.config = config;
+   }
+
+   /*
+    * Switches to another sprite manifest, everything keeps going where it is.
+    */
+   pub fn set_sprites(&mut self, sprites: SpriteManifest) {
+      self.state.set_sprites(&sprites);
+      self.sprites = sprites;
+   }
+
+   pub fn check_extra_lives(&mut self) {
+      for _ in 0..self.extra_lives.check(self.score) {
+         if self.state.award_extra_life() {
+            self.extra_life_flash = EXTRA_LIFE_FLASH_FRAMES;
+         }
+      }
+   }
+
+   /*
+    * Advances the game by one tick of dt seconds. A cleared wave is replaced
+    * by the next one right away.
+    */
+   pub fn tick(&mut self, frame: InputFrame, dt: f32) -> TickOutcome {
+      if !self.state.is_player_alive() {
+         return TickOutcome::GameOver;
+      }
+
+      self.count += 1;
+      let image_dimensions = self.image_dimensions;
+
+      if !self.cheats.god_mode {
+         self.state.check_if_enemy_shot_player(image_dimensions);
+      }
+
+      if self.state.check_if_player_shot_enemy(image_dimensions) {
+         self.score += self.config.scoring.enemy_points;
+         self.check_extra_lives();
+      }
+
+      if self.state.check_if_player_shot_ufo(image_dimensions) {
+         self.score += self.config.scoring.ufo_points;
+         self.check_extra_lives();
+      }
+
+      if self.extra_life_flash > 0 {
+         self.extra_life_flash -= 1;
+      }
+
+      if self.cheats.rapid_fire && frame.fire {
+         self.state.reload_player_bullet();
+      }
+
+      self.state.apply_input(frame, dt, image_dimensions, true);
+      self.state.enemies_shoot();
+
+      if self.state.all_enemies_dead() {
+         // Keeps the events in the order they happened, loading the wave sends some too
+         self.events.append(&mut self.state.drain_events());
+         self.events.push(GameEvent::WaveCleared { wave: self.wave });
+         self.load_next_wave();
+         return TickOutcome::WaveCleared;
+      }
+
+      TickOutcome::Running
+   }
+
+   /*
+    * Ends a game that is still going because the player gave up on it, so
+    * everything listening hears that it's over.
+    */
+   pub fn quit(&mut self) {
+      if self.count > 0 && self.state.is_player_alive() {
+         self.events.append(&mut self.state.drain_events());
+         self.events.push(GameEvent::GameOver { reason: GameOverReason::Quit });
+      }
+   }
+
+   /*
+    * Hands over everything that happened since the last call, in order.
+    */
+   pub fn drain_events(&mut self) -> Vec<GameEvent> {
+      self.events.append(&mut self.state.drain_events());
+      std::mem::take(&mut self.events)
+   }
+}
+
+fn new_formation(config: &GameConfig, sprites: &SpriteManifest, image_dimensions: Vec2) -> Vec<Enemy> {
+   init::init_enemies(sprites,
+                      image_dimensions,
+                      config.enemies.rows,
+                      config.enemies.per_row,
+                      Vec2::splat(config.enemies.scale),
+                      PLAYFIELD_WIDTH,
+                      PLAYFIELD_HEIGHT)
+}
+
+fn new_state(config: &GameConfig, sprites: &SpriteManifest, image_dimensions: Vec2) -> State {
+   let player = init::init_player(sprites,
+                                  image_dimensions,
+                                  Vec2::splat(config.player.scale),
+                                  PLAYFIELD_WIDTH,
+                                  PLAYFIELD_HEIGHT);
+
+   let ufo = init::init_ufo(sprites, Vec2::splat(config.ufo.scale), PLAYFIELD_HEIGHT);
+
+   State::new(config.clone(), new_formation(config, sprites, image_dimensions), player, ufo)
+}
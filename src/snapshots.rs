@@ -0,0 +1,191 @@
+use std::fs;
+use std::path::Path;
+
+use image::RgbaImage;
+
+use crate::bot::Autopilot;
+use crate::config::GameConfig;
+use crate::difficulty::Difficulty;
+use crate::init;
+use crate::input::{self, InputMap};
+use crate::render;
+use crate::scenes::Title;
+use crate::simulation::{GameOptions, Simulation};
+use crate::software::SoftwareRenderer;
+use crate::sprites::SpriteManifest;
+use crate::timestep::TICKS_PER_SECOND;
+
+// Half of the playfield's size, big enough to see what's wrong
+const SNAPSHOT_WIDTH: u32 = 960;
+const SNAPSHOT_SEED: u64 = 1;
+// How long the bot plays before the mid-wave snapshot
+const MID_WAVE_TICKS: u32 = 15 * TICKS_PER_SECOND;
+
+// A channel that is off by at most this much still counts as the same
+const CHANNEL_TOLERANCE: u8 = 8;
+// Fraction of the pixels that may differ before a snapshot doesn't match,
+// so font rasterization differences between machines don't fail it
+const PIXEL_TOLERANCE: f32 = 0.001;
+
+/*
+ * Draws the screens worth keeping an eye on with the software renderer:
+ * the title screen, the HUD over the first wave and a wave the bot has been
+ * playing for a while. Everything comes from the built-in config, sprite
+ * manifest and controls, so the snapshots only change when the code does.
+ */
+fn render_all(resource_dir: &Path) -> Result<Vec<(&'static str, RgbaImage)>, String> {
+   let mut renderer = SoftwareRenderer::load(resource_dir, SNAPSHOT_WIDTH)?;
+   let mut snapshots = Vec::new();
+
+   let controls = InputMap::default();
+   let prompt = |action| match controls.keys(action).first() {
+      Some(key) => input::key_label(*key),
+      None => String::from("---")
+   };
+
+   Title::new().render(&mut renderer, prompt, Difficulty::Normal);
+   snapshots.push(("title", renderer.image().clone()));
+
+   let sprite_sheet = resource_dir.join("space_invaders.png");
+   let image_dimensions = init::png_dimensions(&sprite_sheet)
+                              .map_err(|err| format!("Failed to read {}: {}", sprite_sheet.display(), err))?;
+
+   let mut sim = Simulation::new(image_dimensions, GameConfig::default(), SpriteManifest::default(), GameOptions::default());
+   sim.reset(SNAPSHOT_SEED);
+
+   renderer.clear();
+   render::draw_playfield(&mut renderer, &mut sim, 1.0);
+   render::draw_hud(&mut renderer, &sim, 0);
+   snapshots.push(("hud", renderer.image().clone()));
+
+   let dt = 1.0 / TICKS_PER_SECOND as f32;
+   let mut bot = Autopilot::new();
+
+   for _ in 0..MID_WAVE_TICKS {
+      let frame = bot.frame(&sim, dt);
+      sim.tick(frame, dt);
+      sim.drain_events();
+
+      // The death sprite of an invader is only shown on the first frame
+      // after it died, the window draws every tick so this does too
+      render::draw_playfield(&mut renderer, &mut sim, 1.0);
+   }
+
+   renderer.clear();
+   render::draw_playfield(&mut renderer, &mut sim, 1.0);
+   render::draw_hud(&mut renderer, &sim, 0);
+   snapshots.push(("mid_wave", renderer.image().clone()));
+
+   Ok(snapshots)
+}
+
+/*
+ * Saves the snapshots as dir/<name>.png, e.g. to update the golden images.
+ */
+pub fn write(resource_dir: &Path, dir: &Path) -> Result<(), String> {
+   fs::create_dir_all(dir).map_err(|err| format!("Failed to create {}: {}", dir.display(), err))?;
+
+   for (name, image) in render_all(resource_dir)? {
+      let path = dir.join(format!("{}.png", name));
+      image.save(&path).map_err(|err| format!("Failed to save {}: {}", path.display(), err))?;
+      println!("wrote {}", path.display());
+   }
+
+   Ok(())
+}
+
+/*
+ * Compares the snapshots against the golden images in dir. Each one that
+ * doesn't match is saved next to its golden image as <name>.actual.png.
+ * Returns whether all of them matched.
+ */
+pub fn verify(resource_dir: &Path, dir: &Path) -> Result<bool, String> {
+   let mut all_match = true;
+
+   for (name, image) in render_all(resource_dir)? {
+      let golden_path = dir.join(format!("{}.png", name));
+      let golden = image::open(&golden_path)
+                         .map_err(|err| format!("Failed to load {}: {}", golden_path.display(), err))?
+                         .to_rgba8();
+
+      match difference(&golden, &image) {
+         None => println!("{}: ok", name),
+         Some(reason) => {
+            all_match = false;
+
+            let actual_path = dir.join(format!("{}.actual.png", name));
+            image.save(&actual_path).map_err(|err| format!("Failed to save {}: {}", actual_path.display(), err))?;
+            println!("{}: {}, see {}", name, reason, actual_path.display());
+         }
+      }
+   }
+
+   Ok(all_match)
+}
+
+// Why the images don't match, if they don't
+fn difference(golden: &RgbaImage, actual: &RgbaImage) -> Option<String> {
+   if golden.dimensions() != actual.dimensions() {
+      return Some(format!("size is {}x{} instead of {}x{}",
+                          actual.width(), actual.height(), golden.width(), golden.height()));
+   }
+
+   let different = golden.pixels()
+                         .zip(actual.pixels())
+                         .filter(|(a, b)| a.0.iter().zip(b.0.iter()).any(|(a, b)| a.abs_diff(*b) > CHANNEL_TOLERANCE))
+                         .count();
+
+   let total = (golden.width() * golden.height()) as f32;
+   if different as f32 > total * PIXEL_TOLERANCE {
+      Some(format!("{} pixels differ", different))
+   } else {
+      None
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use std::path::PathBuf;
+
+   use super::*;
+
+   #[test]
+   fn snapshots_match_golden_images() {
+      let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+      let snapshots = render_all(&root.join("src").join("resources")).unwrap();
+      assert_eq!(snapshots.len(), 3);
+
+      for (name, image) in snapshots {
+         let golden_path = root.join("golden").join(format!("{}.png", name));
+         let golden = image::open(&golden_path).unwrap().to_rgba8();
+
+         if let Some(reason) = difference(&golden, &image) {
+            panic!("{} doesn't match {}: {}, update it with --snapshot golden if the change is intended",
+                   name, golden_path.display(), reason);
+         }
+      }
+   }
+
+   #[test]
+   fn difference_tolerates_small_changes() {
+      let golden = RgbaImage::from_pixel(100, 100, image::Rgba([0, 0, 0, 255]));
+
+      let mut slightly_off = golden.clone();
+      slightly_off.put_pixel(0, 0, image::Rgba([CHANNEL_TOLERANCE, 0, 0, 255]));
+      assert_eq!(difference(&golden, &slightly_off), None);
+
+      // One pixel in 10000 is within the pixel tolerance, 11 aren't
+      let mut one_pixel = golden.clone();
+      one_pixel.put_pixel(0, 0, image::Rgba([255, 255, 255, 255]));
+      assert_eq!(difference(&golden, &one_pixel), None);
+
+      let mut eleven_pixels = golden.clone();
+      for x in 0..11 {
+         eleven_pixels.put_pixel(x, 0, image::Rgba([255, 255, 255, 255]));
+      }
+      assert_eq!(difference(&golden, &eleven_pixels), Some(String::from("11 pixels differ")));
+
+      let smaller = RgbaImage::new(50, 100);
+      assert!(difference(&golden, &smaller).is_some());
+   }
+}
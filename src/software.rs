@@ -0,0 +1,192 @@
+use std::fs;
+use std::path::Path;
+
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use ggez::glam::Vec2;
+use ggez::graphics::{Color, DrawParam, Rect, Transform};
+use image::{Rgba, RgbaImage};
+
+use crate::render::Renderer;
+use crate::viewport::{PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT};
+
+/*
+ * Draws the game onto an image in memory instead of the window, so frames
+ * can be saved without a GPU. Sprites are sampled nearest neighbour and text
+ * is rasterized from the same font file, so the output is close to what the
+ * window shows but not the same pixel for pixel. There's no fallback font
+ * here, characters the game font has no glyphs for are left blank.
+ */
+pub struct SoftwareRenderer {
+   canvas: RgbaImage,
+   sprite_sheet: RgbaImage,
+   font: FontVec,
+   // Image pixels per playfield unit
+   scale: f32
+}
+
+impl SoftwareRenderer {
+   /*
+    * Loads the sprite sheet and the font from the resources directory. The
+    * image is width pixels wide and as high as the playfield's aspect ratio
+    * makes it.
+    */
+   pub fn load(resource_dir: &Path, width: u32) -> Result<Self, String> {
+      let sheet_path = resource_dir.join("space_invaders.png");
+      let sprite_sheet = image::open(&sheet_path)
+                                .map_err(|err| format!("Failed to load {}: {}", sheet_path.display(), err))?
+                                .to_rgba8();
+
+      let font_path = resource_dir.join("font.TTF");
+      let font_data = fs::read(&font_path).map_err(|err| format!("Failed to read {}: {}", font_path.display(), err))?;
+      let font = FontVec::try_from_vec(font_data).map_err(|err| format!("Failed to load {}: {}", font_path.display(), err))?;
+
+      let scale = width as f32 / PLAYFIELD_WIDTH;
+      let height = (PLAYFIELD_HEIGHT * scale).round() as u32;
+
+      Ok(SoftwareRenderer {
+         canvas: RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 255])),
+         sprite_sheet,
+         font,
+         scale
+      })
+   }
+
+   /*
+    * Back to black, like the window at the start of a frame.
+    */
+   pub fn clear(&mut self) {
+      for pixel in self.canvas.pixels_mut() {
+         *pixel = Rgba([0, 0, 0, 255]);
+      }
+   }
+
+   pub fn image(&self) -> &RgbaImage {
+      &self.canvas
+   }
+
+   // Blends color over the pixel, coverage scales its alpha
+   fn blend(&mut self, x: i64, y: i64, color: [f32; 4], coverage: f32) {
+      if x < 0 || y < 0 || x >= self.canvas.width() as i64 || y >= self.canvas.height() as i64 {
+         return;
+      }
+
+      let alpha = (color[3] * coverage).clamp(0.0, 1.0);
+      if alpha <= 0.0 {
+         return;
+      }
+
+      let pixel = self.canvas.get_pixel_mut(x as u32, y as u32);
+      for (channel, value) in pixel.0.iter_mut().zip(color).take(3) {
+         let under = *channel as f32 / 255.0;
+         *channel = ((value * alpha + under * (1.0 - alpha)) * 255.0).round() as u8;
+      }
+   }
+}
+
+impl Renderer for SoftwareRenderer {
+   fn draw_sprite(&mut self, param: DrawParam) {
+      // Sprites are only ever moved and scaled
+      let (dest, scale) = match param.transform {
+         Transform::Values { dest, scale, .. } => (Vec2::new(dest.x, dest.y), Vec2::new(scale.x, scale.y)),
+         Transform::Matrix(_) => return
+      };
+
+      if scale.x == 0.0 || scale.y == 0.0 {
+         return;
+      }
+
+      let sheet_width = self.sprite_sheet.width() as f32;
+      let sheet_height = self.sprite_sheet.height() as f32;
+      let src = Rect::new(param.src.x * sheet_width, param.src.y * sheet_height,
+                          param.src.w * sheet_width, param.src.h * sheet_height);
+
+      // Where the sprite ends up in image pixels
+      let left = dest.x.min(dest.x + src.w * scale.x) * self.scale;
+      let right = dest.x.max(dest.x + src.w * scale.x) * self.scale;
+      let top = dest.y.min(dest.y + src.h * scale.y) * self.scale;
+      let bottom = dest.y.max(dest.y + src.h * scale.y) * self.scale;
+
+      let tint = [param.color.r, param.color.g, param.color.b, param.color.a];
+
+      for y in top.round() as i64..bottom.round() as i64 {
+         let sheet_y = src.y + ((y as f32 + 0.5) / self.scale - dest.y) / scale.y;
+         if sheet_y < src.y || sheet_y >= src.y + src.h || sheet_y < 0.0 || sheet_y >= sheet_height {
+            continue;
+         }
+
+         for x in left.round() as i64..right.round() as i64 {
+            let sheet_x = src.x + ((x as f32 + 0.5) / self.scale - dest.x) / scale.x;
+            // Source rects that reach past the sheet are transparent there
+            if sheet_x < src.x || sheet_x >= src.x + src.w || sheet_x < 0.0 || sheet_x >= sheet_width {
+               continue;
+            }
+
+            let texel = self.sprite_sheet.get_pixel(sheet_x as u32, sheet_y as u32).0;
+            let color = [
+               texel[0] as f32 / 255.0 * tint[0],
+               texel[1] as f32 / 255.0 * tint[1],
+               texel[2] as f32 / 255.0 * tint[2],
+               texel[3] as f32 / 255.0 * tint[3]
+            ];
+
+            self.blend(x, y, color, 1.0);
+         }
+      }
+   }
+
+   fn draw_text(&mut self, text: &str, scale: f32, position: Vec2, color: Color) {
+      let font = self.font.as_scaled(PxScale::from(scale * self.scale));
+      let baseline = position.y * self.scale + font.ascent();
+      let color = [color.r, color.g, color.b, color.a];
+
+      let mut x = position.x * self.scale;
+      let mut previous = None;
+      let mut coverage = Vec::new();
+
+      for c in text.chars() {
+         let id = font.glyph_id(c);
+         if let Some(previous) = previous {
+            x += font.kern(previous, id);
+         }
+
+         let glyph = id.with_scale_and_position(font.scale(), point(x, baseline));
+         x += font.h_advance(id);
+         previous = Some(id);
+
+         // Glyph 0 is the font's box for missing characters
+         if id.0 == 0 {
+            continue;
+         }
+
+         if let Some(outline) = font.outline_glyph(glyph) {
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, value| {
+               coverage.push((bounds.min.x as i64 + gx as i64, bounds.min.y as i64 + gy as i64, value));
+            });
+         }
+      }
+
+      for (x, y, value) in coverage {
+         self.blend(x, y, color, value);
+      }
+   }
+
+   fn text_size(&mut self, text: &str, scale: f32) -> Vec2 {
+      let font = self.font.as_scaled(PxScale::from(scale));
+
+      let mut width = 0.0;
+      let mut previous = None;
+
+      for c in text.chars() {
+         let id = font.glyph_id(c);
+         if let Some(previous) = previous {
+            width += font.kern(previous, id);
+         }
+
+         width += font.h_advance(id);
+         previous = Some(id);
+      }
+
+      Vec2::new(width, font.ascent() - font.descent())
+   }
+}
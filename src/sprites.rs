@@ -0,0 +1,99 @@
+use std::fs;
+use std::path::Path;
+
+use ggez::graphics::{DrawParam, Rect};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::Sprite;
+
+/*
+ * Where every sprite is on the sprite sheet, read from a TOML file. Each
+ * sprite is a list of animation frames, each frame a rect [x, y, w, h] in
+ * fractions of the sheet's size:
+ *
+ *    player = [[0.25, 0.9, 0.16, 0.1]]
+ */
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpriteManifest {
+   pub player: Vec<[f32; 4]>,
+   pub player_death: Vec<[f32; 4]>,
+   // The first row of invaders and every other one after it
+   pub invader_a: Vec<[f32; 4]>,
+   // The rows in between
+   pub invader_b: Vec<[f32; 4]>,
+   pub invader_death: Vec<[f32; 4]>,
+   pub bullet: Vec<[f32; 4]>,
+   pub ufo: Vec<[f32; 4]>
+}
+
+impl Default for SpriteManifest {
+   fn default() -> Self {
+      SpriteManifest {
+         player: vec![[0.25, 0.9, 0.16, 0.1]],
+         player_death: vec![[0.40, 0.9, 0.19, 0.12]],
+         invader_a: vec![[0.0, 0.0, 0.25, 0.12], [0.25, 0.0, 0.25, 0.12]],
+         invader_b: vec![[0.0, 0.175, 0.25, 0.12], [0.25, 0.175, 0.25, 0.12]],
+         invader_death: vec![[0.62, 0.9, 0.20, 0.12]],
+         bullet: vec![[0.83, 0.55, 0.15, 0.12]],
+         ufo: vec![[0.0, 0.9, 0.24, 0.1]]
+      }
+   }
+}
+
+impl SpriteManifest {
+   /*
+    * Reads and validates a manifest file.
+    */
+   pub fn load(path: &Path) -> Result<SpriteManifest, String> {
+      let contents = fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+      let manifest = toml::from_str::<SpriteManifest>(&contents).map_err(|err| format!("Invalid sprites {}: {}", path.display(), err))?;
+
+      manifest.validate().map_err(|err| format!("Invalid sprites {}: {}", path.display(), err))?;
+      Ok(manifest)
+   }
+
+   /*
+    * Every sprite needs at least one frame, and every frame has to start on
+    * the sheet. Some of the original frames reach a little past its edges.
+    */
+   pub fn validate(&self) -> Result<(), String> {
+      let mut problems = Vec::new();
+
+      for (name, frames) in self.all() {
+         if frames.is_empty() {
+            problems.push(format!("{} has no frames", name));
+         }
+
+         for [x, y, w, h] in frames {
+            let inside = (0.0..1.0).contains(x) && (0.0..1.0).contains(y) && *w > 0.0 && *w <= 1.0 && *h > 0.0 && *h <= 1.0;
+
+            if !inside {
+               problems.push(format!("{} has a frame that isn't on the sheet: [{}, {}, {}, {}]", name, x, y, w, h));
+            }
+         }
+      }
+
+      if problems.is_empty() {
+         Ok(())
+      } else {
+         Err(problems.join("; "))
+      }
+   }
+
+   fn all(&self) -> [(&'static str, &Vec<[f32; 4]>); 7] {
+      [
+         ("player", &self.player),
+         ("player_death", &self.player_death),
+         ("invader_a", &self.invader_a),
+         ("invader_b", &self.invader_b),
+         ("invader_death", &self.invader_death),
+         ("bullet", &self.bullet),
+         ("ufo", &self.ufo)
+      ]
+   }
+}
+
+pub fn sprite(frames: &[[f32; 4]]) -> Sprite {
+   Sprite::new(frames.iter().map(|[x, y, w, h]| DrawParam::default().src(Rect::new(*x, *y, *w, *h))).collect())
+}
@@ -1,56 +1,151 @@
 #![allow(dead_code)]
 
-use crate::WINDOW_SIZE_WIDTH;
-use crate::WINDOW_SIZE_HEIGHT;
+use crate::viewport::PLAYFIELD_WIDTH;
+use crate::viewport::PLAYFIELD_HEIGHT;
+use crate::config::GameConfig;
 use crate::entities;
-use crate::input;
-use core::sync::atomic::{AtomicU8, AtomicI8, Ordering};
-use rand::Rng;
-
-pub const NUM_ROWS: u8 = 3;
-
-static NUM_ENEMIES_ON_ROW: AtomicU8 = AtomicU8::new(0);
-static ENEMY_MOVEMENT_DIRECTION: AtomicI8 = AtomicI8::new(1);
-static ENEMY_SPEED: AtomicU8 = AtomicU8::new(2);
+use crate::events::{GameEvent, GameOverReason, InvaderKind};
+use crate::input_source::InputFrame;
+use crate::render::Renderer;
+use crate::sprites::{self, SpriteManifest};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+// Seconds between two notes of the march with a full formation at the
+// starting speed, and the shortest it gets.
+pub const MARCH_SLOWEST_INTERVAL: f32 = 0.9;
+pub const MARCH_FASTEST_INTERVAL: f32 = 0.12;
 
 use entities::Enemy;
 use entities::Player;
-use ggez::Context;
+use entities::Ufo;
 use ggez::glam::Vec2;
-use ggez::graphics::Canvas;
-use ggez::graphics::Image;
+use ggez::graphics::Rect;
+
+/*
+ * What something on the playfield is, for frontends that don't draw the
+ * sprites.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityKind {
+   Player,
+   Invader,
+   Ufo,
+   PlayerBullet,
+   EnemyBullet
+}
+
+/*
+ * What the debug overlay shows about one entity.
+ */
+pub struct EntityInfo {
+   pub kind: EntityKind,
+   pub dest_rect: Rect,
+   // Where it is on the playfield, if it's on it at all
+   pub bounds: Option<Rect>,
+   // As get_scale returns it, which is what the collision checks use
+   pub scale: Vec2,
+   // The animation frame shown and how many there are
+   pub frame: (usize, usize),
+   // Size of the frame as (height, width) in fractions of the sprite sheet
+   pub frame_size: Vec2,
+   pub flags: Vec<(&'static str, bool)>
+}
 pub struct State {
    enemies: Vec<Enemy>,
    player: Box<Player>,
-   num_enemies: u8
+   ufo: Ufo,
+   // Seconds until the next ufo flight
+   ufo_timer: f32,
+   ufo_from_left: bool,
+   // How many invaders there are on a row of the current formation
+   enemies_per_row: u8,
+   // 1 while the formation marches right, -1 while it marches left
+   enemy_direction: i8,
+   enemy_speed: u8,
+   events: Vec<GameEvent>,
+   config: GameConfig,
+   // Everything random in the game comes from here, see reseed
+   rng: StdRng
 }
 
 impl State {
-   pub fn new(num_enemies: u8, enemies: Vec<Enemy>, player: Box<Player>) -> Self {
-      NUM_ENEMIES_ON_ROW.store(num_enemies / NUM_ROWS, Ordering::Relaxed);
-
+   pub fn new(config: GameConfig, enemies: Vec<Enemy>, player: Box<Player>, ufo: Ufo) -> Self {
       State {
-         enemies: enemies,
-         num_enemies: num_enemies,
-         player: player
+         enemies,
+         enemies_per_row: config.enemies.per_row,
+         enemy_direction: 1,
+         enemy_speed: config.enemies.starting_speed,
+         player,
+         ufo,
+         ufo_timer: config.ufo.interval,
+         ufo_from_left: true,
+         events: Vec::new(),
+         config,
+         rng: StdRng::seed_from_u64(0)
+      }
+   }
+
+   pub fn config(&self) -> &GameConfig {
+      &self.config
+   }
+
+   /*
+    * Speeds and fire rates change right away, a differently sized formation
+    * only comes with the next set_formation.
+    */
+   pub fn set_config(&mut self, config: GameConfig) {
+      self.config = config;
+   }
+
+   /*
+    * Swaps every sprite for the ones in the manifest, keeping where
+    * everything is and what it's doing.
+    */
+   pub fn set_sprites(&mut self, manifest: &SpriteManifest) {
+      let per_row = self.enemies_per_row.max(1) as usize;
+
+      for (index, enemy) in self.enemies.iter_mut().enumerate() {
+         let alive = match invader_kind(index, per_row) {
+            InvaderKind::A => &manifest.invader_a,
+            InvaderKind::B => &manifest.invader_b
+         };
+         enemy.set_sprites(sprites::sprite(alive), sprites::sprite(&manifest.invader_death), sprites::sprite(&manifest.bullet));
       }
+
+      self.player.set_sprites(sprites::sprite(&manifest.player), sprites::sprite(&manifest.player_death), sprites::sprite(&manifest.bullet));
+      self.ufo.set_sprite(sprites::sprite(&manifest.ufo));
    }
 
-   pub fn check_player_movement(&self, ctx: &Context) -> i8 {
-      input::update_movement(ctx)
+   pub fn reseed(&mut self, seed: u64) {
+      self.rng = StdRng::seed_from_u64(seed);
    }
 
-   pub fn check_player_shoot(&self, ctx: &Context) -> bool {
-      input::update_shoot(ctx)
+   /*
+    * Hands over everything that happened since the last call.
+    */
+   pub fn drain_events(&mut self) -> Vec<GameEvent> {
+      std::mem::take(&mut self.events)
    }
 
-   pub fn draw_enemies(&mut self, canvas: &mut Canvas, count: u64, image: &Image) {
+   /*
+    * Applies the player's input for one tick and moves everything.
+    */
+   pub fn apply_input(&mut self, frame: InputFrame, dt: f32, image_dimensions: Vec2, scaled: bool) {
+      if frame.fire {
+         self.player_shoot();
+      }
+
+      self.update(self.config.player.speed * frame.movement, dt, image_dimensions, scaled);
+   }
+
+   pub fn draw_enemies(&mut self, renderer: &mut dyn Renderer, count: u64, alpha: f32) {
       for enemy in self.enemies.as_mut_slice() {
-         enemy.draw(canvas, count, image);
+         enemy.draw(renderer, count, alpha);
 
          if !enemy.is_alive() && !enemy.get_death_frame_drawn() {
             // This also sets the death_frame_drawn to true
-            enemy.draw_die(canvas, count, image);
+            enemy.draw_die(renderer, count, alpha);
          }
       }
    }
@@ -65,30 +160,60 @@ impl State {
       true
    }
 
+   /*
+    * Seconds between two notes of the march. Gets shorter as invaders die
+    * and as the formation speeds up from wave to wave.
+    */
+   pub fn march_interval(&self) -> f32 {
+      let alive = self.enemies.iter().filter(|enemy| enemy.is_alive()).count() as f32;
+      let alive_fraction = alive / self.enemies.len().max(1) as f32;
+      let speed_factor = self.enemy_speed.max(1) as f32 / self.config.enemies.starting_speed.max(1) as f32;
+
+      let interval = MARCH_FASTEST_INTERVAL + (MARCH_SLOWEST_INTERVAL - MARCH_FASTEST_INTERVAL) * alive_fraction;
+      (interval / speed_factor).max(MARCH_FASTEST_INTERVAL)
+   }
+
    pub fn revive_player(&mut self, position: Vec2) {
       self.player.revive(position);
    }
 
-   pub fn draw_player(&mut self, canvas: &mut Canvas, count: u64, image: &Image) {
+   pub fn configure_player_lives(&mut self, starting_lives: u8, max_lives: u8) {
+      self.player.configure_lives(starting_lives, max_lives);
+   }
+
+   pub fn award_extra_life(&mut self) -> bool {
+      if self.player.gain_life() {
+         self.events.push(GameEvent::ExtraLife);
+         return true;
+      }
+
+      false
+   }
+
+   pub fn draw_player(&mut self, renderer: &mut dyn Renderer, count: u64, alpha: f32) {
       if self.player.is_alive() {
-         self.player.draw(canvas, count, image);
+         self.player.draw(renderer, count, alpha);
       } else {
-         self.player.draw_die(canvas, count, image);
+         self.player.draw_die(renderer, count, alpha);
       }
    }
 
    pub fn check_if_player_shot_enemy(&mut self, image_dimensions: Vec2) -> bool {
-      for enemy in self.enemies.as_mut_slice() {
-         if enemy.is_alive() {
-            if self.player.bullet_collision_with_enemy(&enemy, image_dimensions) {
-               self.player.set_bullet_in_air(false);
-               enemy.die();
-               return true;
-            }
+      let per_row = self.enemies_per_row.max(1) as usize;
+
+      for (index, enemy) in self.enemies.iter_mut().enumerate() {
+         if enemy.is_alive() && self.player.bullet_collision_with_enemy(enemy, image_dimensions) {
+            self.player.set_bullet_in_air(false);
+            enemy.die();
+            self.events.push(GameEvent::InvaderKilled {
+               kind: invader_kind(index, per_row),
+               points: self.config.scoring.enemy_points
+            });
+            return true;
          }
       }
 
-      return false;
+      false
    }
 
    pub fn check_if_enemy_shot_player(&mut self, image_dimensions: Vec2) -> bool {
@@ -96,21 +221,98 @@ impl State {
          if enemy.bullet_collision_with_player(&self.player, image_dimensions) {
             self.player.lose_life();
             enemy.set_bullet_in_air(false);
+            self.events.push(GameEvent::PlayerHit);
+            self.events.push(GameEvent::LifeLost { lives_left: self.player.get_lives() });
+
+            if !self.player.is_alive() {
+               self.events.push(GameEvent::GameOver { reason: GameOverReason::OutOfLives });
+            }
+
             return true;
          }
       }
 
-      return false;
+      false
+   }
+
+   pub fn check_if_player_shot_ufo(&mut self, image_dimensions: Vec2) -> bool {
+      if self.player.bullet_collision_with_ufo(&self.ufo, image_dimensions) {
+         self.player.set_bullet_in_air(false);
+         self.ufo.remove();
+         self.events.push(GameEvent::UfoKilled { points: self.config.scoring.ufo_points });
+         return true;
+      }
+
+      false
+   }
+
+   pub fn draw_ufo(&mut self, renderer: &mut dyn Renderer, count: u64, alpha: f32) {
+      self.ufo.draw(renderer, count, alpha);
    }
 
-   pub fn move_enemies(&mut self, screen_width: f32, scaled: bool, image_dimensions: Vec2) {
-      let first_enemy_on_row_coords = self.enemies[0].get_coords();
-      let last_enemy_on_row = &self.enemies[(NUM_ENEMIES_ON_ROW.load(Ordering::Relaxed) - 1) as usize];
-      let last_enemy_on_row_coords = self.enemies[(NUM_ENEMIES_ON_ROW.load(Ordering::Relaxed) - 1) as usize].get_coords();
+   /*
+    * Flies the ufo if it's out and sends it out when its time has come.
+    */
+   pub fn move_ufo(&mut self, screen_width: f32, dt: f32, image_dimensions: Vec2) {
+      self.ufo.save_position();
 
-      if first_enemy_on_row_coords.x < 0.0 || last_enemy_on_row_coords.x + image_dimensions.y * last_enemy_on_row.get_scale().y * last_enemy_on_row.get_frame_dimensions().unwrap().y > screen_width {
-         let current_enemy_movement = ENEMY_MOVEMENT_DIRECTION.load(Ordering::Relaxed);
-         ENEMY_MOVEMENT_DIRECTION.store(current_enemy_movement * -1, Ordering::Relaxed);
+      if self.ufo.is_active() {
+         if self.ufo.update(self.config.ufo.speed, dt, screen_width, image_dimensions) {
+            self.events.push(GameEvent::UfoLeft);
+         }
+
+         return;
+      }
+
+      self.ufo_timer -= dt;
+
+      if self.ufo_timer <= 0.0 && self.player.is_alive() {
+         self.ufo.spawn(self.ufo_from_left, screen_width, image_dimensions);
+         self.ufo_from_left = !self.ufo_from_left;
+         self.ufo_timer = self.next_ufo_interval();
+         self.events.push(GameEvent::UfoSpawned);
+      }
+   }
+
+   /*
+    * Takes the ufo off the screen and restarts its timer, e.g. for a new wave.
+    */
+   pub fn reset_ufo(&mut self) {
+      if self.ufo.is_active() {
+         self.ufo.remove();
+         self.events.push(GameEvent::UfoLeft);
+      }
+
+      self.ufo_timer = self.next_ufo_interval();
+   }
+
+   fn next_ufo_interval(&mut self) -> f32 {
+      let ufo = &self.config.ufo;
+
+      if ufo.interval_spread > 0.0 {
+         ufo.interval + self.rng.gen_range(0.0..ufo.interval_spread)
+      } else {
+         ufo.interval
+      }
+   }
+
+   pub fn set_enemy_direction_right(&mut self) {
+      self.enemy_direction = 1;
+   }
+
+   pub fn reset_enemy_movement_speed(&mut self, speed: u8) {
+      self.enemy_speed = speed;
+   }
+
+   pub fn increase_enemy_movement_speed(&mut self, speed: u8) {
+      self.enemy_speed = self.enemy_speed.saturating_add(speed);
+   }
+
+   pub fn move_enemies(&mut self, screen_width: f32, dt: f32, scaled: bool, image_dimensions: Vec2) {
+      let (left, right) = self.formation_edges(image_dimensions);
+
+      if left < 0.0 || right > screen_width {
+         self.enemy_direction = -self.enemy_direction;
 
 
          // Move the enemies one row down.
@@ -120,8 +322,10 @@ impl State {
          }
       }
 
+      let speed_unit = self.config.enemies.speed_unit;
+
       for enemy in self.enemies.as_mut_slice() {
-         let x: f32 = ENEMY_MOVEMENT_DIRECTION.load(Ordering::Relaxed) as f32 * ENEMY_SPEED.load(Ordering::Relaxed) as f32;
+         let x: f32 = self.enemy_direction as f32 * self.enemy_speed as f32 * speed_unit * dt;
          enemy.translate(Vec2::new(x, 0.0), scaled);
 
          // if enemy.is_alive() && enemy.get_coords().y >= self.player.get_coords().y {
@@ -130,6 +334,19 @@ impl State {
       }
    }
 
+   /*
+    * Where move_enemies sees the formation, left and right: the first and
+    * last invader on the top row, dead or alive. It turns around once
+    * either is past the edge of the screen.
+    */
+   pub fn formation_edges(&self, image_dimensions: Vec2) -> (f32, f32) {
+      let first_enemy_on_row = &self.enemies[0];
+      let last_enemy_on_row = &self.enemies[(self.enemies_per_row - 1) as usize];
+
+      (first_enemy_on_row.get_coords().x,
+       last_enemy_on_row.get_coords().x + image_dimensions.y * last_enemy_on_row.get_scale().y * last_enemy_on_row.get_frame_dimensions().unwrap().y)
+   }
+
    pub fn enemies_shoot(&mut self) {
       for enemy in self.enemies.as_mut_slice() {
          if enemy.is_alive() && enemy.is_bullet_in_air() {
@@ -145,11 +362,13 @@ impl State {
          }
       }
 
-      if enemies_alive.len() == 0 {
+      if enemies_alive.is_empty() {
          return;
       }
 
-      if enemies_alive.len() <= 5 {
+      let fire = &self.config.enemies;
+
+      if enemies_alive.len() <= fire.all_fire_at {
          for alive in enemies_alive {
             alive.shoot();
          }
@@ -159,20 +378,23 @@ impl State {
 
       let mut _enemies_to_shoot: u16 = 0;
 
-      // These values can be played around with :)
-      if enemies_alive.len() > 20 {
-         _enemies_to_shoot = 5;
+      // These values can be played around with in the config :)
+      if enemies_alive.len() > fire.volley_threshold {
+         _enemies_to_shoot = fire.large_volley;
       } else {
-         _enemies_to_shoot = 3;
+         _enemies_to_shoot = fire.small_volley;
       }
 
+      // A reloaded config can ask for more than the current formation has
+      _enemies_to_shoot = _enemies_to_shoot.min(self.enemies.len() as u16);
+
       for _ in 0.._enemies_to_shoot {
-         let mut index = rand::thread_rng().gen_range(0.._enemies_to_shoot);
+         let mut index = self.rng.gen_range(0.._enemies_to_shoot);
 
          let mut enemy = &mut self.enemies.as_mut_slice()[index as usize];
 
          if enemy.is_bullet_in_air() {
-            index = rand::thread_rng().gen_range(0.._enemies_to_shoot);
+            index = self.rng.gen_range(0.._enemies_to_shoot);
 
             enemy = &mut self.enemies.as_mut_slice()[index as usize];
             if enemy.is_bullet_in_air() {
@@ -198,27 +420,203 @@ impl State {
       self.player.translate(Vec2::new(x, 0.0), scaled);
    }
 
-   pub fn set_enemies(&mut self, enemies: Vec<Enemy>) {
+   pub fn set_formation(&mut self, enemies: Vec<Enemy>, enemies_per_row: u8) {
       self.enemies = enemies;
+      self.enemies_per_row = enemies_per_row;
    }
 
    pub fn player_shoot(&mut self) {
+      if self.player.is_alive() && !self.player.is_bullet_in_air() {
+         self.events.push(GameEvent::ShotFired);
+      }
+
       self.player.shoot();
    }
 
+   /*
+    * Takes the player's bullet out of the air so the next shot leaves from
+    * the player right away.
+    */
+   pub fn reload_player_bullet(&mut self) {
+      self.player.reload_bullet();
+   }
+
+   #[cfg(feature = "dev")]
+   pub fn set_player_lives(&mut self, lives: u8) {
+      self.player.set_lives(lives);
+   }
+
+   #[cfg(feature = "dev")]
+   pub fn kill_all_enemies(&mut self) {
+      for enemy in self.enemies.iter_mut().filter(|enemy| enemy.is_alive()) {
+         enemy.die();
+      }
+   }
+
+   /*
+    * Sends the ufo across right away, unless it's already flying.
+    */
+   #[cfg(feature = "dev")]
+   pub fn spawn_ufo(&mut self, screen_width: f32, image_dimensions: Vec2) {
+      if self.ufo.is_active() {
+         return;
+      }
+
+      self.ufo.spawn(self.ufo_from_left, screen_width, image_dimensions);
+      self.ufo_from_left = !self.ufo_from_left;
+      self.events.push(GameEvent::UfoSpawned);
+   }
+
    pub fn is_player_alive(&self) -> bool {
       self.player.is_alive()
    }
 
-   pub fn update(&mut self, x: f32, image_dimensions: Vec2, scaled: bool) {
-      self.player.update(9.0, scaled);
+   /*
+    * Advances the simulation by dt seconds. velocity_x is the player's
+    * horizontal velocity in units per second.
+    */
+   pub fn update(&mut self, velocity_x: f32, dt: f32, image_dimensions: Vec2, scaled: bool) {
+      self.player.save_position();
+      self.player.update(self.config.player.bullet_speed, dt, scaled);
+
+      let enemy_bullet_speed = self.config.enemies.bullet_speed;
 
       for enemy in self.enemies.as_mut_slice() {
-         enemy.update(6.0, WINDOW_SIZE_HEIGHT, scaled);
+         enemy.save_position();
+         enemy.update(enemy_bullet_speed, dt, PLAYFIELD_HEIGHT, scaled);
       }
 
-      self.move_enemies(WINDOW_SIZE_WIDTH, scaled, image_dimensions);
-      self.move_player(x, WINDOW_SIZE_WIDTH, image_dimensions, scaled)
+      self.move_enemies(PLAYFIELD_WIDTH, dt, scaled, image_dimensions);
+      self.move_ufo(PLAYFIELD_WIDTH, dt, image_dimensions);
+      self.move_player(velocity_x * dt, PLAYFIELD_WIDTH, image_dimensions, scaled)
+   }
+
+   /*
+    * Where everything is on the playfield, for anything that needs to look
+    * at the game without being part of it (bots, debug views).
+    */
+   pub fn player_bounds(&self, image_dimensions: Vec2) -> Rect {
+      self.player.get_bounds(image_dimensions)
+   }
+
+   pub fn player_bullet_bounds(&self, image_dimensions: Vec2) -> Option<Rect> {
+      self.player.get_bullet_bounds(image_dimensions)
+   }
+
+   pub fn enemy_bounds(&self, image_dimensions: Vec2) -> Vec<Rect> {
+      self.enemies.iter().filter(|enemy| enemy.is_alive()).map(|enemy| enemy.get_bounds(image_dimensions)).collect()
+   }
+
+   pub fn enemy_bullet_bounds(&self, image_dimensions: Vec2) -> Vec<Rect> {
+      self.enemies.iter().filter_map(|enemy| enemy.get_bullet_bounds(image_dimensions)).collect()
+   }
+
+   /*
+    * Everything on the playfield, back to front.
+    */
+   pub fn hitboxes(&self, image_dimensions: Vec2) -> Vec<(EntityKind, Rect)> {
+      let mut hitboxes: Vec<(EntityKind, Rect)> = self.enemy_bounds(image_dimensions)
+                                                      .into_iter()
+                                                      .map(|bounds| (EntityKind::Invader, bounds))
+                                                      .collect();
+
+      hitboxes.extend(self.ufo_bounds(image_dimensions).map(|bounds| (EntityKind::Ufo, bounds)));
+      hitboxes.extend(self.enemy_bullet_bounds(image_dimensions).into_iter().map(|bounds| (EntityKind::EnemyBullet, bounds)));
+      hitboxes.extend(self.player_bullet_bounds(image_dimensions).map(|bounds| (EntityKind::PlayerBullet, bounds)));
+
+      if self.player.is_alive() {
+         hitboxes.push((EntityKind::Player, self.player_bounds(image_dimensions)));
+      }
+
+      hitboxes
+   }
+
+   /*
+    * One entry per place in the formation, dead invaders included, so the
+    * lists keep the same length and order for the whole wave.
+    */
+   pub fn enemy_alive(&self) -> Vec<bool> {
+      self.enemies.iter().map(|enemy| enemy.is_alive()).collect()
+   }
+
+   pub fn enemy_bullet_slots(&self, image_dimensions: Vec2) -> Vec<Option<Rect>> {
+      self.enemies.iter().map(|enemy| enemy.get_bullet_bounds(image_dimensions)).collect()
+   }
+
+   pub fn ufo_bounds(&self, image_dimensions: Vec2) -> Option<Rect> {
+      self.ufo.is_active().then(|| self.ufo.get_bounds(image_dimensions))
+   }
+
+   /*
+    * The frontmost entity covering point, with the index of the invader it
+    * is or that fired it (0 for the others).
+    */
+   pub fn entity_at(&self, point: Vec2, image_dimensions: Vec2) -> Option<(EntityKind, usize)> {
+      if self.player.is_alive() && self.player_bounds(image_dimensions).contains(point) {
+         return Some((EntityKind::Player, 0));
+      }
+
+      if self.player_bullet_bounds(image_dimensions).is_some_and(|bounds| bounds.contains(point)) {
+         return Some((EntityKind::PlayerBullet, 0));
+      }
+
+      let enemy_bullet = self.enemies.iter().position(|enemy| enemy.get_bullet_bounds(image_dimensions).is_some_and(|bounds| bounds.contains(point)));
+      if let Some(index) = enemy_bullet {
+         return Some((EntityKind::EnemyBullet, index));
+      }
+
+      if self.ufo_bounds(image_dimensions).is_some_and(|bounds| bounds.contains(point)) {
+         return Some((EntityKind::Ufo, 0));
+      }
+
+      self.enemies.iter()
+                  .position(|enemy| enemy.is_alive() && enemy.get_bounds(image_dimensions).contains(point))
+                  .map(|index| (EntityKind::Invader, index))
+   }
+
+   /*
+    * The details of an entity found with entity_at, at tick count.
+    */
+   pub fn inspect(&self, kind: EntityKind, index: usize, count: u64, image_dimensions: Vec2) -> Option<EntityInfo> {
+      let info = match kind {
+         EntityKind::Player => EntityInfo {
+            kind,
+            dest_rect: self.player.get_dest_rect(),
+            bounds: self.player.is_alive().then(|| self.player_bounds(image_dimensions)),
+            scale: self.player.get_scale(),
+            frame: self.player.animation_frame(count),
+            frame_size: self.player.get_frame_dimensions().ok()?,
+            flags: vec![("alive", self.player.is_alive()), ("bullet in air", self.player.get_bullet().in_air())]
+         },
+         EntityKind::PlayerBullet => bullet_info(kind, self.player.get_bullet(), count, image_dimensions)?,
+         EntityKind::Invader => {
+            let enemy = self.enemies.get(index)?;
+
+            EntityInfo {
+               kind,
+               dest_rect: enemy.get_dest_rect(),
+               bounds: enemy.is_alive().then(|| enemy.get_bounds(image_dimensions)),
+               scale: enemy.get_scale(),
+               frame: enemy.animation_frame(count),
+               frame_size: enemy.get_frame_dimensions().ok()?,
+               flags: vec![("alive", enemy.is_alive()),
+                           ("bullet in air", enemy.is_bullet_in_air()),
+                           ("death frame drawn", enemy.get_death_frame_drawn())]
+            }
+         },
+         EntityKind::EnemyBullet => bullet_info(kind, self.enemies.get(index)?.get_bullet(), count, image_dimensions)?,
+         EntityKind::Ufo => EntityInfo {
+            kind,
+            dest_rect: self.ufo.get_dest_rect(),
+            bounds: self.ufo_bounds(image_dimensions),
+            scale: self.ufo.get_scale(),
+            frame: self.ufo.animation_frame(count),
+            frame_size: self.ufo.get_frame_dimensions().ok()?,
+            flags: vec![("active", self.ufo.is_active())]
+         }
+      };
+
+      Some(info)
    }
 
    pub fn get_player_lives(&self) -> u8 {
@@ -230,18 +628,20 @@ impl State {
    }
 }
 
-pub fn set_enemy_direction_right() {
-   let current_value = ENEMY_MOVEMENT_DIRECTION.load(Ordering::Relaxed);
-
-   ENEMY_MOVEMENT_DIRECTION.store(current_value * -1, Ordering::Relaxed);
+// The kind of the invader at index in a formation of rows per_row wide,
+// the same way init_enemies alternates them
+fn invader_kind(index: usize, per_row: usize) -> InvaderKind {
+   if (index / per_row).is_multiple_of(2) { InvaderKind::A } else { InvaderKind::B }
 }
 
-pub fn reset_enemy_movement_speed() {
-   ENEMY_SPEED.store(2, Ordering::Relaxed);
+fn bullet_info(kind: EntityKind, bullet: &entities::Bullet, count: u64, image_dimensions: Vec2) -> Option<EntityInfo> {
+   Some(EntityInfo {
+      kind,
+      dest_rect: bullet.get_dest_rect(),
+      bounds: bullet.in_air().then(|| bullet.get_bounds(image_dimensions)),
+      scale: bullet.get_scale(),
+      frame: bullet.animation_frame(count),
+      frame_size: bullet.get_frame_dimensions().ok()?,
+      flags: vec![("in air", bullet.in_air())]
+   })
 }
-
-pub fn increase_enemy_movement_speed(speed: u8) {
-   let current_value = ENEMY_SPEED.load(Ordering::Relaxed);
-
-   ENEMY_SPEED.store(current_value + speed, Ordering::Relaxed);
-}
\ No newline at end of file
@@ -0,0 +1,192 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::{GameEvent, GameOverReason, InvaderKind, Subscriber};
+use crate::timestep::TICKS_PER_SECOND;
+
+/*
+ * Totals over every game played, kept in a TOML file next to the high
+ * scores. Everything is counted from the game events. A game is only added
+ * to the totals, and the file saved, once it ends, so a game that's still
+ * going when the window is closed isn't counted.
+ */
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserStatistics {
+   pub games_played: u64,
+   // Time spent in games, in ticks
+   pub play_ticks: u64,
+   pub shots_fired: u64,
+   // Shots that hit an invader or a ufo
+   pub hits: u64,
+   pub ufos_destroyed: u64,
+   pub highest_wave: u32,
+   // The only thing that kills the player is invader fire
+   pub lives_lost: u64,
+   pub invaders_a_killed: u64,
+   pub invaders_b_killed: u64,
+   // How the games ended
+   pub games_lost: u64,
+   pub games_quit: u64,
+   // The game in progress
+   #[serde(skip)]
+   game: Option<Box<UserStatistics>>,
+   #[serde(skip)]
+   path: PathBuf
+}
+
+impl UserStatistics {
+   pub fn load(path: &Path) -> Self {
+      let mut statistics = if path.exists() {
+         match fs::read_to_string(path).map_err(|err| err.to_string())
+                                       .and_then(|contents| toml::from_str::<UserStatistics>(&contents).map_err(|err| err.to_string())) {
+            Err(err) => {
+               eprintln!("Failed to load statistics from {}, starting over: {}", path.display(), err);
+               UserStatistics::default()
+            },
+            Ok(statistics) => statistics
+         }
+      } else {
+         UserStatistics::default()
+      };
+
+      statistics.path = PathBuf::from(path);
+      statistics
+   }
+
+   pub fn save(&self) {
+      let contents = match toml::to_string_pretty(self) {
+         Err(err) => {
+            eprintln!("Failed to serialize statistics: {}", err);
+            return;
+         },
+         Ok(contents) => contents
+      };
+
+      if let Err(err) = fs::write(&self.path, contents) {
+         eprintln!("Failed to write statistics to {}: {}", self.path.display(), err);
+      }
+   }
+
+   pub fn play_seconds(&self) -> u64 {
+      self.play_ticks / TICKS_PER_SECOND as u64
+   }
+
+   /*
+    * Percentage of the shots fired that hit something.
+    */
+   pub fn accuracy(&self) -> u64 {
+      (self.hits * 100).checked_div(self.shots_fired).unwrap_or(0)
+   }
+
+   fn add(&mut self, other: &UserStatistics) {
+      self.games_played += other.games_played;
+      self.play_ticks += other.play_ticks;
+      self.shots_fired += other.shots_fired;
+      self.hits += other.hits;
+      self.ufos_destroyed += other.ufos_destroyed;
+      self.highest_wave = self.highest_wave.max(other.highest_wave);
+      self.lives_lost += other.lives_lost;
+      self.invaders_a_killed += other.invaders_a_killed;
+      self.invaders_b_killed += other.invaders_b_killed;
+      self.games_lost += other.games_lost;
+      self.games_quit += other.games_quit;
+   }
+
+   fn count(&mut self, tick: u64, event: &GameEvent) {
+      match *event {
+         GameEvent::GameStarted { wave } => self.highest_wave = self.highest_wave.max(wave),
+         GameEvent::ShotFired => self.shots_fired += 1,
+         GameEvent::InvaderKilled { kind, .. } => {
+            self.hits += 1;
+            match kind {
+               InvaderKind::A => self.invaders_a_killed += 1,
+               InvaderKind::B => self.invaders_b_killed += 1
+            }
+         },
+         GameEvent::UfoKilled { .. } => {
+            self.hits += 1;
+            self.ufos_destroyed += 1;
+         },
+         GameEvent::LifeLost { .. } => self.lives_lost += 1,
+         GameEvent::WaveCleared { wave } => self.highest_wave = self.highest_wave.max(wave + 1),
+         GameEvent::GameOver { reason } => {
+            self.games_played += 1;
+            self.play_ticks += tick;
+            match reason {
+               GameOverReason::OutOfLives => self.games_lost += 1,
+               GameOverReason::Quit => self.games_quit += 1
+            }
+         },
+         GameEvent::PlayerHit | GameEvent::UfoSpawned | GameEvent::UfoLeft | GameEvent::ExtraLife => {}
+      }
+   }
+}
+
+impl Subscriber for UserStatistics {
+   fn notify(&mut self, tick: u64, event: &GameEvent) {
+      // A game that never ends (e.g. because it stopped getting events once
+      // a cheat was used) is thrown away with the next one
+      if let GameEvent::GameStarted { .. } = event {
+         self.game = Some(Box::default());
+      }
+
+      if let Some(game) = self.game.as_mut() {
+         game.count(tick, event);
+      }
+
+      if let GameEvent::GameOver { .. } = event {
+         if let Some(game) = self.game.take() {
+            self.add(&game);
+            self.save();
+         }
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn play(stats: &mut UserStatistics, events: &[GameEvent]) {
+      for (tick, event) in events.iter().enumerate() {
+         stats.notify(tick as u64, event);
+      }
+   }
+
+   #[test]
+   fn games_count_once_they_are_over() {
+      let path = std::env::temp_dir().join(format!("space_invaders_statistics_{}.toml", std::process::id()));
+      let mut stats = UserStatistics::load(&path);
+
+      play(&mut stats, &[
+         GameEvent::GameStarted { wave: 1 },
+         GameEvent::ShotFired,
+         GameEvent::InvaderKilled { kind: InvaderKind::B, points: 10 },
+         GameEvent::ShotFired
+      ]);
+      assert_eq!(stats.shots_fired, 0);
+
+      // Started over without the first game ending, e.g. after a cheat
+      play(&mut stats, &[
+         GameEvent::GameStarted { wave: 3 },
+         GameEvent::ShotFired,
+         GameEvent::WaveCleared { wave: 3 },
+         GameEvent::GameOver { reason: GameOverReason::Quit }
+      ]);
+
+      assert_eq!(stats.games_played, 1);
+      assert_eq!(stats.games_quit, 1);
+      assert_eq!(stats.shots_fired, 1);
+      assert_eq!(stats.hits, 0);
+      assert_eq!(stats.highest_wave, 4);
+      assert_eq!(stats.play_ticks, 3);
+
+      let saved = UserStatistics::load(&path);
+      fs::remove_file(&path).unwrap();
+      assert_eq!(saved.games_played, 1);
+      assert_eq!(saved.shots_fired, 1);
+   }
+}
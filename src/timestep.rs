@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+pub const TICKS_PER_SECOND: u32 = 60;
+
+// Don't try to catch up on more than this much time in one frame, otherwise
+// a long stall (window drag, breakpoint) makes the game fast forward.
+const MAX_FRAME_TIME: f32 = 0.25;
+
+/*
+ * Accumulator for running the simulation at a fixed rate, independent of
+ * how often the frame is drawn. Feed it the frame time with advance(), then
+ * run one simulation tick for every time tick() returns true. alpha() tells
+ * how far between the last two ticks the current frame is, for interpolating
+ * positions when drawing.
+ */
+pub struct FixedTimestep {
+   accumulator: f32,
+   dt: f32
+}
+
+impl FixedTimestep {
+   pub fn new(ticks_per_second: u32) -> Self {
+      FixedTimestep {
+         accumulator: 0.0,
+         dt: 1.0 / ticks_per_second as f32
+      }
+   }
+
+   pub fn advance(&mut self, frame_time: Duration) {
+      self.accumulator += frame_time.as_secs_f32().min(MAX_FRAME_TIME);
+   }
+
+   pub fn tick(&mut self) -> bool {
+      if self.accumulator >= self.dt {
+         self.accumulator -= self.dt;
+         return true;
+      }
+
+      false
+   }
+
+   pub fn dt(&self) -> f32 {
+      self.dt
+   }
+
+   pub fn alpha(&self) -> f32 {
+      (self.accumulator / self.dt).clamp(0.0, 1.0)
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn ticks(timestep: &mut FixedTimestep) -> u32 {
+      std::iter::from_fn(|| timestep.tick().then_some(())).count() as u32
+   }
+
+   #[test]
+   fn runs_one_tick_per_step_of_frame_time() {
+      let mut timestep = FixedTimestep::new(TICKS_PER_SECOND);
+
+      timestep.advance(Duration::from_millis(10));
+      assert_eq!(ticks(&mut timestep), 0);
+
+      timestep.advance(Duration::from_millis(10));
+      assert_eq!(ticks(&mut timestep), 1);
+
+      // Leftovers add up over frames
+      for _ in 0..10 {
+         timestep.advance(Duration::from_millis(50));
+      }
+      assert_eq!(ticks(&mut timestep), 30);
+   }
+
+   #[test]
+   fn long_stalls_are_cut_short() {
+      let mut timestep = FixedTimestep::new(TICKS_PER_SECOND);
+      let most_ticks = (MAX_FRAME_TIME * TICKS_PER_SECOND as f32).round() as u32;
+
+      timestep.advance(Duration::from_secs(10));
+      let caught_up = ticks(&mut timestep);
+      assert!(caught_up <= most_ticks && caught_up >= most_ticks - 1, "{} ticks", caught_up);
+
+      // Nothing of the stall is left over for the next frame
+      timestep.advance(Duration::from_millis(1));
+      assert!(ticks(&mut timestep) <= 1);
+      assert!(timestep.alpha() < 1.0);
+   }
+
+   #[test]
+   fn alpha_stays_between_ticks() {
+      let mut timestep = FixedTimestep::new(TICKS_PER_SECOND);
+      assert_eq!(timestep.alpha(), 0.0);
+
+      for millis in [1, 7, 16, 17, 33, 100, 250, 1000] {
+         timestep.advance(Duration::from_millis(millis));
+         ticks(&mut timestep);
+
+         let alpha = timestep.alpha();
+         assert!((0.0..1.0).contains(&alpha), "alpha {} after {} ms", alpha, millis);
+      }
+
+      let mut timestep = FixedTimestep::new(TICKS_PER_SECOND);
+      timestep.advance(Duration::from_secs_f32(timestep.dt() / 2.0));
+      assert!((timestep.alpha() - 0.5).abs() < 0.01);
+   }
+}
@@ -0,0 +1,64 @@
+use ggez::Context;
+use ggez::glam::Vec2;
+use ggez::graphics::{self, Canvas, Color, DrawParam, Drawable, Rect};
+
+use crate::render;
+use crate::viewport::{PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT};
+
+// How long a toast stays on screen, in ticks.
+const TOAST_TICKS: u32 = 180;
+// Toasts fade out during their last ticks.
+const TOAST_FADE_TICKS: u32 = 30;
+const MAX_TOASTS: usize = 4;
+
+struct Toast {
+   text: String,
+   ticks_left: u32
+}
+
+/*
+ * Short notices shown at the bottom of the screen on top of every scene,
+ * for things that happen outside of the game itself.
+ */
+#[derive(Default)]
+pub struct Toasts {
+   toasts: Vec<Toast>
+}
+
+impl Toasts {
+   pub fn push(&mut self, text: &str) {
+      if self.toasts.len() == MAX_TOASTS {
+         self.toasts.remove(0);
+      }
+
+      self.toasts.push(Toast { text: text.to_string(), ticks_left: TOAST_TICKS });
+   }
+
+   pub fn update(&mut self) {
+      for toast in self.toasts.iter_mut() {
+         toast.ticks_left = toast.ticks_left.saturating_sub(1);
+      }
+
+      self.toasts.retain(|toast| toast.ticks_left > 0);
+   }
+
+   pub fn draw(&self, canvas: &mut Canvas, ctx: &mut Context) {
+      let mut y = PLAYFIELD_HEIGHT * 0.93;
+
+      // Newest at the bottom
+      for toast in self.toasts.iter().rev() {
+         let opacity = (toast.ticks_left as f32 / TOAST_FADE_TICKS as f32).min(1.0);
+
+         let text = render::text_in_font(&toast.text, 36.0);
+
+         let dimensions = text.dimensions(ctx).unwrap();
+         let x = (PLAYFIELD_WIDTH - dimensions.w) / 2.0;
+         y -= dimensions.h + 20.0;
+
+         canvas.draw(&graphics::Quad, DrawParam::default()
+                     .dest_rect(Rect::new(x - 20.0, y - 10.0, dimensions.w + 40.0, dimensions.h + 20.0))
+                     .color(Color::new(0.0, 0.0, 0.0, 0.8 * opacity)));
+         canvas.draw(&text, DrawParam::default().dest(Vec2::new(x, y)).color(Color::new(1.0, 1.0, 1.0, opacity)));
+      }
+   }
+}
@@ -0,0 +1,579 @@
+use std::io::{self, BufWriter, Stdout, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crossterm::{cursor, event, execute, queue, style, terminal};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags};
+use crossterm::style::Color;
+use ggez::input::keyboard::KeyCode as GameKey;
+
+use crate::config::GameConfig;
+use crate::highscores::{HighScoreTable, NAME_LENGTH};
+use crate::init;
+use crate::input::{self, Action, InputMap};
+use crate::input_source::InputFrame;
+use crate::raster::Grid;
+use crate::simulation::{GameOptions, Simulation, TickOutcome};
+use crate::sprites::SpriteManifest;
+use crate::state::EntityKind;
+use crate::timestep::{FixedTimestep, TICKS_PER_SECOND};
+
+// The terminal is redrawn about 30 times a second, the game still ticks 60
+const DRAW_INTERVAL: Duration = Duration::from_millis(33);
+
+// Most terminals never report a key being let go, only repeated presses
+// while it's held. A press counts as held for about as long as it takes the
+// key to start repeating, every repeat a little longer.
+const PRESS_HOLD_TICKS: u32 = 20;
+const REPEAT_HOLD_TICKS: u32 = 6;
+
+// The playfield is 16:9, a cell is two pixels high
+const ASPECT_WIDTH: usize = 16;
+const ASPECT_HEIGHT: usize = 9;
+
+/*
+ * Plays the game in a terminal, drawn with half block characters: each
+ * character cell shows two pixels of the playfield, one above the other.
+ * Runs the same simulation as the window, without sound, with the key
+ * bindings from the settings file. Q (unless it's bound to something) and
+ * Ctrl+C quit, the way closing the window does.
+ */
+pub fn run(resource_dir: &Path, config: GameConfig, sprites: SpriteManifest, options: GameOptions, seed: Option<u64>, score_file: &str, controls: InputMap) -> Result<(), String> {
+   let sprite_sheet = resource_dir.join("space_invaders.png");
+   let image_dimensions = init::png_dimensions(&sprite_sheet)
+                              .map_err(|err| format!("Failed to read {}: {}", sprite_sheet.display(), err))?;
+
+   let mut game = TuiGame {
+      sim: Simulation::new(image_dimensions, config, sprites, options),
+      high_scores: HighScoreTable::load(score_file),
+      controls,
+      seed,
+      mode: Mode::Playing,
+      left: HeldKey::default(),
+      right: HeldKey::default(),
+      fire: false,
+      quit: false,
+      drawn: Vec::new()
+   };
+   game.reset();
+
+   let terminal = Terminal::open().map_err(|err| format!("Failed to set up the terminal: {}", err))?;
+   let result = game.run(&terminal);
+   drop(terminal);
+
+   result.map_err(|err| format!("Terminal error: {}", err))
+}
+
+/*
+ * Raw mode and the alternate screen for as long as it lives, so the
+ * terminal is put back even if the game panics.
+ */
+struct Terminal {
+   // Key releases are reported, see HeldKey
+   releases: bool
+}
+
+impl Terminal {
+   fn open() -> io::Result<Self> {
+      terminal::enable_raw_mode()?;
+      execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+
+      let releases = terminal::supports_keyboard_enhancement().unwrap_or(false);
+      if releases {
+         execute!(io::stdout(), event::PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES))?;
+      }
+
+      Ok(Terminal { releases })
+   }
+}
+
+impl Drop for Terminal {
+   fn drop(&mut self) {
+      if self.releases {
+         let _ = execute!(io::stdout(), event::PopKeyboardEnhancementFlags);
+      }
+
+      let _ = execute!(io::stdout(), style::ResetColor, cursor::Show, terminal::LeaveAlternateScreen);
+      let _ = terminal::disable_raw_mode();
+   }
+}
+
+/*
+ * A movement key. Held until it's released if the terminal says so,
+ * otherwise for a few ticks after each press.
+ */
+#[derive(Default)]
+struct HeldKey {
+   ticks: u32
+}
+
+impl HeldKey {
+   fn handle(&mut self, kind: KeyEventKind, releases: bool) {
+      match kind {
+         KeyEventKind::Press if releases => self.ticks = u32::MAX,
+         KeyEventKind::Press if self.ticks > 0 => self.ticks = self.ticks.max(REPEAT_HOLD_TICKS),
+         KeyEventKind::Press => self.ticks = PRESS_HOLD_TICKS,
+         KeyEventKind::Release => self.ticks = 0,
+         KeyEventKind::Repeat => {}
+      }
+   }
+
+   fn release(&mut self) {
+      self.ticks = 0;
+   }
+
+   fn is_held(&self) -> bool {
+      self.ticks > 0
+   }
+
+   fn tick(&mut self) {
+      if self.ticks != u32::MAX {
+         self.ticks = self.ticks.saturating_sub(1);
+      }
+   }
+}
+
+enum Mode {
+   Playing,
+   Paused,
+   // The initials typed so far
+   NameEntry(String),
+   // Where the score ended up in the high scores, if it did
+   GameOver(Option<usize>)
+}
+
+struct TuiGame {
+   sim: Simulation,
+   high_scores: HighScoreTable,
+   controls: InputMap,
+   seed: Option<u64>,
+   mode: Mode,
+   left: HeldKey,
+   right: HeldKey,
+   fire: bool,
+   quit: bool,
+   // What each cell of the playfield shows on screen right now, only cells
+   // that change are drawn again
+   drawn: Vec<(Option<EntityKind>, Option<EntityKind>)>
+}
+
+impl TuiGame {
+   fn reset(&mut self) {
+      self.sim.reset(self.seed.unwrap_or_else(rand::random));
+      self.mode = Mode::Playing;
+   }
+
+   fn run(&mut self, terminal: &Terminal) -> io::Result<()> {
+      let mut out = BufWriter::new(io::stdout());
+      let mut timestep = FixedTimestep::new(TICKS_PER_SECOND);
+      let mut last_frame = Instant::now();
+      let mut last_draw: Option<Instant> = None;
+
+      while !self.quit {
+         let wait = last_draw.map_or(Duration::ZERO, |last_draw| DRAW_INTERVAL.saturating_sub(last_draw.elapsed()));
+
+         if event::poll(wait)? {
+            self.handle(event::read()?, terminal.releases, &mut out)?;
+
+            while event::poll(Duration::ZERO)? {
+               self.handle(event::read()?, terminal.releases, &mut out)?;
+            }
+         }
+
+         let now = Instant::now();
+         timestep.advance(now - last_frame);
+         last_frame = now;
+
+         while timestep.tick() {
+            self.update(timestep.dt());
+         }
+
+         if last_draw.is_none_or(|last_draw| last_draw.elapsed() >= DRAW_INTERVAL) {
+            self.draw(&mut out)?;
+            last_draw = Some(Instant::now());
+         }
+      }
+
+      Ok(())
+   }
+
+   fn handle(&mut self, event: Event, releases: bool, out: &mut BufWriter<Stdout>) -> io::Result<()> {
+      match event {
+         Event::Key(key) => self.handle_key(key, releases),
+         // Whatever was drawn outside the new size would stay on screen
+         Event::Resize(_, _) => {
+            queue!(out, terminal::Clear(terminal::ClearType::All))?;
+            self.drawn.clear();
+         },
+         _ => {}
+      }
+
+      Ok(())
+   }
+
+   fn handle_key(&mut self, key: KeyEvent, releases: bool) {
+      // Raw mode swallows the signal, so Ctrl+C is just another key
+      if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+         self.quit = true;
+         return;
+      }
+
+      let actions: Vec<Action> = match game_key(key.code) {
+         Some(game_key) => self.controls.actions_for(game_key).collect(),
+         None => Vec::new()
+      };
+
+      // Initials are typed, not picked with the menu keys like in the window
+      if let Mode::NameEntry(name) = &mut self.mode {
+         if key.kind != KeyEventKind::Press {
+            return;
+         }
+
+         match key.code {
+            KeyCode::Char(letter) if letter.is_ascii_alphabetic() && name.len() < NAME_LENGTH => {
+               name.push(letter.to_ascii_uppercase());
+            },
+            // Letters are only ever typed, even if they're bound to something
+            KeyCode::Char(letter) if letter.is_ascii_alphabetic() => {},
+            KeyCode::Backspace => {
+               name.pop();
+            },
+            _ if actions.contains(&Action::Confirm) && !name.is_empty() => {
+               let position = self.high_scores.insert(name, self.sim.score, self.sim.options.difficulty);
+               self.mode = Mode::GameOver(position);
+            },
+            _ if actions.contains(&Action::Back) => self.mode = Mode::GameOver(None),
+            _ => {}
+         }
+
+         return;
+      }
+
+      if actions.is_empty() {
+         if matches!(key.code, KeyCode::Char('q') | KeyCode::Char('Q')) && key.kind != KeyEventKind::Release {
+            self.quit = true;
+         }
+
+         return;
+      }
+
+      // Only movement cares about keys being let go
+      let pressed = key.kind != KeyEventKind::Release;
+
+      for action in actions {
+         match (&self.mode, action) {
+            (Mode::Playing, Action::MoveLeft) => {
+               self.left.handle(key.kind, releases);
+
+               if pressed {
+                  self.right.release();
+               }
+            },
+            (Mode::Playing, Action::MoveRight) => {
+               self.right.handle(key.kind, releases);
+
+               if pressed {
+                  self.left.release();
+               }
+            },
+            (Mode::Playing, Action::Fire) if pressed => self.fire = true,
+            (Mode::Playing, Action::Pause) if key.kind == KeyEventKind::Press => {
+               self.mode = Mode::Paused;
+               return;
+            },
+            (Mode::Paused, Action::Pause | Action::Confirm | Action::Back) if key.kind == KeyEventKind::Press => {
+               self.left.release();
+               self.right.release();
+               self.mode = Mode::Playing;
+               return;
+            },
+            (Mode::GameOver(_), Action::Confirm) if key.kind == KeyEventKind::Press => {
+               self.reset();
+               return;
+            },
+            (Mode::GameOver(_), Action::Back) if key.kind == KeyEventKind::Press => {
+               self.quit = true;
+               return;
+            },
+            _ => {}
+         }
+      }
+   }
+
+   fn update(&mut self, dt: f32) {
+      if !matches!(self.mode, Mode::Playing) {
+         return;
+      }
+
+      let movement = self.right.is_held() as u8 as f32 - self.left.is_held() as u8 as f32;
+      let frame = InputFrame::new(movement, std::mem::take(&mut self.fire));
+
+      self.left.tick();
+      self.right.tick();
+
+      let outcome = self.sim.tick(frame, dt);
+      self.sim.drain_events();
+
+      if outcome == TickOutcome::GameOver {
+         self.mode = if self.high_scores.qualifies(self.sim.score, self.sim.options.difficulty) {
+            Mode::NameEntry(String::new())
+         } else {
+            Mode::GameOver(None)
+         };
+      }
+   }
+
+   fn draw(&mut self, out: &mut BufWriter<Stdout>) -> io::Result<()> {
+      let (columns, rows) = terminal::size()?;
+      let (columns, rows) = (columns as usize, rows as usize);
+
+      queue!(out, terminal::BeginSynchronizedUpdate)?;
+
+      // One row for the score on top and one for help at the bottom
+      if columns < ASPECT_WIDTH || rows < 4 {
+         queue!(out, cursor::MoveTo(0, 0), terminal::Clear(terminal::ClearType::All), style::Print("Too small"))?;
+         self.drawn.clear();
+         queue!(out, terminal::EndSynchronizedUpdate)?;
+         return out.flush();
+      }
+
+      let difficulty = self.sim.options.difficulty;
+      let high_score = self.high_scores.best(difficulty).max(self.sim.score);
+      let hud = format!(" SCORE {}   HIGH SCORE {}   LIVES {}   WAVE {}   {}",
+                        self.sim.score, high_score, self.sim.state.get_player_lives(), self.sim.wave, difficulty.name().to_uppercase());
+      print_line(out, 0, &hud, columns)?;
+
+      // As large as fits while keeping the playfield's shape
+      let max_height = (rows - 2) * 2;
+      let width = columns.min(max_height * ASPECT_WIDTH / ASPECT_HEIGHT);
+      let height = (width * ASPECT_HEIGHT / ASPECT_WIDTH).min(max_height);
+      let cell_rows = height.div_ceil(2);
+      let left = (columns - width) / 2;
+      let top = 1 + (rows - 2 - cell_rows) / 2;
+
+      let mut grid = Grid::new(width, height, None);
+      for (kind, bounds) in self.sim.state.hitboxes(self.sim.image_dimensions()) {
+         grid.fill(bounds, Some(kind));
+      }
+
+      if self.drawn.len() != width * cell_rows {
+         self.drawn = vec![(Some(EntityKind::Player), Some(EntityKind::Player)); width * cell_rows];
+      }
+
+      // Over SSH every byte counts, so colors are only set when they change
+      let mut colors = None;
+
+      for row in 0..cell_rows {
+         let mut next_x = None;
+
+         for x in 0..width {
+            let upper = grid.get(x, row * 2);
+            let lower = if row * 2 + 1 < height { grid.get(x, row * 2 + 1) } else { None };
+
+            if self.drawn[row * width + x] == (upper, lower) {
+               continue;
+            }
+
+            self.drawn[row * width + x] = (upper, lower);
+
+            if next_x != Some(x) {
+               queue!(out, cursor::MoveTo((left + x) as u16, (top + row) as u16))?;
+            }
+
+            if colors != Some((upper, lower)) {
+               queue!(out, style::SetForegroundColor(color(upper)), style::SetBackgroundColor(color(lower)))?;
+               colors = Some((upper, lower));
+            }
+
+            queue!(out, style::Print('▀'))?;
+            next_x = Some(x + 1);
+         }
+      }
+
+      queue!(out, style::ResetColor)?;
+
+      print_line(out, rows - 1, &self.status(), columns)?;
+      queue!(out, terminal::EndSynchronizedUpdate)?;
+      out.flush()
+   }
+
+   fn status(&self) -> String {
+      match &self.mode {
+         Mode::Playing => format!(" Move with {} and {}, shoot with {}, {} pauses, Q quits",
+                                  self.prompt(Action::MoveLeft), self.prompt(Action::MoveRight), self.prompt(Action::Fire), self.prompt(Action::Pause)),
+         Mode::Paused => format!(" PAUSED   {} to go on, Q to quit", self.prompt(Action::Pause)),
+         Mode::NameEntry(name) => format!(" NEW HIGH SCORE {}!   Type your initials: {:_<width$}   {} saves",
+                                          self.sim.score, name, self.prompt(Action::Confirm), width = NAME_LENGTH),
+         Mode::GameOver(Some(position)) => format!(" GAME OVER   Score {}, number {} on the high scores   {} plays again, Q quits",
+                                                   self.sim.score, position + 1, self.prompt(Action::Confirm)),
+         Mode::GameOver(None) => format!(" GAME OVER   Score {}   {} plays again, Q quits", self.sim.score, self.prompt(Action::Confirm))
+      }
+   }
+
+   // The first key bound to action, like Game::prompt
+   fn prompt(&self, action: Action) -> String {
+      match self.controls.keys(action).first() {
+         Some(key) => input::key_label(*key),
+         None => String::from("---")
+      }
+   }
+}
+
+/*
+ * The key in the window's terms, for the keys a terminal reports. Letters
+ * come in upper case with Shift, modifier keys on their own not at all.
+ */
+fn game_key(code: KeyCode) -> Option<GameKey> {
+   const LETTERS: [GameKey; 26] = [
+      GameKey::A, GameKey::B, GameKey::C, GameKey::D, GameKey::E, GameKey::F, GameKey::G, GameKey::H, GameKey::I,
+      GameKey::J, GameKey::K, GameKey::L, GameKey::M, GameKey::N, GameKey::O, GameKey::P, GameKey::Q, GameKey::R,
+      GameKey::S, GameKey::T, GameKey::U, GameKey::V, GameKey::W, GameKey::X, GameKey::Y, GameKey::Z
+   ];
+   const DIGITS: [GameKey; 10] = [
+      GameKey::Key0, GameKey::Key1, GameKey::Key2, GameKey::Key3, GameKey::Key4,
+      GameKey::Key5, GameKey::Key6, GameKey::Key7, GameKey::Key8, GameKey::Key9
+   ];
+   const FUNCTION_KEYS: [GameKey; 12] = [
+      GameKey::F1, GameKey::F2, GameKey::F3, GameKey::F4, GameKey::F5, GameKey::F6,
+      GameKey::F7, GameKey::F8, GameKey::F9, GameKey::F10, GameKey::F11, GameKey::F12
+   ];
+
+   let key = match code {
+      KeyCode::Char(c) if c.is_ascii_alphabetic() => LETTERS[(c.to_ascii_lowercase() as u8 - b'a') as usize],
+      KeyCode::Char(c) if c.is_ascii_digit() => DIGITS[(c as u8 - b'0') as usize],
+      KeyCode::Char(' ') => GameKey::Space,
+      KeyCode::Char('\'') => GameKey::Apostrophe,
+      KeyCode::Char(',') => GameKey::Comma,
+      KeyCode::Char('-') => GameKey::Minus,
+      KeyCode::Char('.') => GameKey::Period,
+      KeyCode::Char('/') => GameKey::Slash,
+      KeyCode::Char(';') => GameKey::Semicolon,
+      KeyCode::Char('=') => GameKey::Equals,
+      KeyCode::Char('[') => GameKey::LBracket,
+      KeyCode::Char(']') => GameKey::RBracket,
+      KeyCode::Char('\\') => GameKey::Backslash,
+      KeyCode::Char('`') => GameKey::Grave,
+      KeyCode::F(n) if (1..=12).contains(&n) => FUNCTION_KEYS[n as usize - 1],
+      KeyCode::Left => GameKey::Left,
+      KeyCode::Right => GameKey::Right,
+      KeyCode::Up => GameKey::Up,
+      KeyCode::Down => GameKey::Down,
+      KeyCode::Enter => GameKey::Return,
+      KeyCode::Esc => GameKey::Escape,
+      KeyCode::Backspace => GameKey::Back,
+      KeyCode::Tab => GameKey::Tab,
+      KeyCode::Insert => GameKey::Insert,
+      KeyCode::Delete => GameKey::Delete,
+      KeyCode::Home => GameKey::Home,
+      KeyCode::End => GameKey::End,
+      KeyCode::PageUp => GameKey::PageUp,
+      KeyCode::PageDown => GameKey::PageDown,
+      _ => return None
+   };
+
+   Some(key)
+}
+
+/*
+ * Writes a whole row, cut off or padded with spaces to the terminal width.
+ */
+fn print_line(out: &mut impl Write, row: usize, text: &str, columns: usize) -> io::Result<()> {
+   let line: String = text.chars().chain(std::iter::repeat(' ')).take(columns).collect();
+   queue!(out, cursor::MoveTo(0, row as u16), style::ResetColor, style::Print(line))
+}
+
+fn color(kind: Option<EntityKind>) -> Color {
+   match kind {
+      Some(EntityKind::Player) => Color::Green,
+      Some(EntityKind::Invader) => Color::White,
+      Some(EntityKind::Ufo) => Color::Red,
+      Some(EntityKind::PlayerBullet) => Color::Yellow,
+      Some(EntityKind::EnemyBullet) => Color::Magenta,
+      None => Color::Black
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use std::path::PathBuf;
+
+   use super::*;
+
+   fn tui_game(controls: InputMap) -> TuiGame {
+      let sprite_sheet = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src").join("resources").join("space_invaders.png");
+      let score_file = std::env::temp_dir().join(format!("space_invaders_tui_scores_{}.txt", std::process::id()));
+      let high_scores = HighScoreTable::load(&score_file.to_string_lossy());
+      let _ = std::fs::remove_file(&score_file);
+
+      let mut game = TuiGame {
+         sim: Simulation::new(init::png_dimensions(&sprite_sheet).unwrap(), GameConfig::default(), SpriteManifest::default(), GameOptions::default()),
+         high_scores,
+         controls,
+         seed: Some(1),
+         mode: Mode::Playing,
+         left: HeldKey::default(),
+         right: HeldKey::default(),
+         fire: false,
+         quit: false,
+         drawn: Vec::new()
+      };
+      game.reset();
+      game
+   }
+
+   fn press(game: &mut TuiGame, code: KeyCode) {
+      game.handle_key(KeyEvent::new(code, KeyModifiers::NONE), false);
+   }
+
+   #[test]
+   fn terminal_keys_translate_to_window_keys() {
+      assert_eq!(game_key(KeyCode::Char('a')), Some(GameKey::A));
+      assert_eq!(game_key(KeyCode::Char('Z')), Some(GameKey::Z));
+      assert_eq!(game_key(KeyCode::Char('7')), Some(GameKey::Key7));
+      assert_eq!(game_key(KeyCode::Char(' ')), Some(GameKey::Space));
+      assert_eq!(game_key(KeyCode::Enter), Some(GameKey::Return));
+      assert_eq!(game_key(KeyCode::Esc), Some(GameKey::Escape));
+      assert_eq!(game_key(KeyCode::F(12)), Some(GameKey::F12));
+      assert_eq!(game_key(KeyCode::F(13)), None);
+      assert_eq!(game_key(KeyCode::Char('é')), None);
+      assert_eq!(game_key(KeyCode::CapsLock), None);
+   }
+
+   #[test]
+   fn keys_go_through_the_bindings() {
+      let mut controls = InputMap::default();
+      controls.bind(Action::Fire, GameKey::J);
+      controls.bind(Action::MoveLeft, GameKey::H);
+      let mut game = tui_game(controls);
+
+      press(&mut game, KeyCode::Char(' '));
+      assert!(!game.fire);
+      press(&mut game, KeyCode::Char('j'));
+      assert!(game.fire);
+
+      press(&mut game, KeyCode::Left);
+      assert!(!game.left.is_held());
+      press(&mut game, KeyCode::Char('h'));
+      assert!(game.left.is_held());
+
+      press(&mut game, KeyCode::Esc);
+      assert!(matches!(game.mode, Mode::Paused));
+      press(&mut game, KeyCode::Char('p'));
+      assert!(matches!(game.mode, Mode::Playing));
+      assert!(!game.quit);
+
+      press(&mut game, KeyCode::Char('q'));
+      assert!(game.quit);
+   }
+
+   #[test]
+   fn q_only_quits_while_it_isnt_bound() {
+      let mut controls = InputMap::default();
+      controls.bind(Action::Fire, GameKey::Q);
+      let mut game = tui_game(controls);
+
+      press(&mut game, KeyCode::Char('q'));
+      assert!(game.fire);
+      assert!(!game.quit);
+   }
+}
@@ -0,0 +1,144 @@
+use ggez::glam::Vec2;
+use ggez::graphics::{Canvas, Rect};
+
+/*
+ * The game is laid out in a fixed logical playfield. Whatever the size of
+ * the window, the playfield is scaled uniformly to fit and centered, with
+ * black bars filling the rest (letterboxing).
+ */
+pub const PLAYFIELD_WIDTH: f32 = 1920.0;
+pub const PLAYFIELD_HEIGHT: f32 = 1080.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+   // Scale from logical units to window pixels
+   scale: f32,
+   // Where the playfield starts in the window, in pixels
+   offset: Vec2
+}
+
+impl Viewport {
+   pub fn new(window_width: f32, window_height: f32) -> Self {
+      let scale = (window_width / PLAYFIELD_WIDTH).min(window_height / PLAYFIELD_HEIGHT).max(f32::EPSILON);
+
+      Viewport {
+         scale,
+         offset: Vec2::new((window_width - PLAYFIELD_WIDTH * scale) / 2.0,
+                           (window_height - PLAYFIELD_HEIGHT * scale) / 2.0)
+      }
+   }
+
+   /*
+    * The screen coordinates to give the canvas so that drawing in logical
+    * units lands inside the letterboxed playfield.
+    */
+   pub fn screen_coordinates(&self) -> Rect {
+      Rect::new(-self.offset.x / self.scale,
+                -self.offset.y / self.scale,
+                (PLAYFIELD_WIDTH * self.scale + 2.0 * self.offset.x) / self.scale,
+                (PLAYFIELD_HEIGHT * self.scale + 2.0 * self.offset.y) / self.scale)
+   }
+
+   /*
+    * The playfield in window pixels.
+    */
+   pub fn playfield_pixels(&self) -> Rect {
+      Rect::new(self.offset.x, self.offset.y, PLAYFIELD_WIDTH * self.scale, PLAYFIELD_HEIGHT * self.scale)
+   }
+
+   /*
+    * Where a point in window pixels, e.g. the mouse, is on the playfield in
+    * logical units.
+    */
+   pub fn to_logical(self, point: Vec2) -> Vec2 {
+      (point - self.offset) / self.scale
+   }
+
+   /*
+    * Sets up the canvas to draw in logical units and clips anything that
+    * would spill into the black bars.
+    */
+   pub fn apply(&self, canvas: &mut Canvas) {
+      canvas.set_screen_coordinates(self.screen_coordinates());
+
+      let clip = self.playfield_pixels();
+      canvas.set_scissor_rect(Rect::new(clip.x.floor(), clip.y.floor(), clip.w.ceil(), clip.h.ceil())).ok();
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn assert_close(actual: f32, expected: f32) {
+      assert!((actual - expected).abs() < 0.01, "{} instead of {}", actual, expected);
+   }
+
+   fn assert_rect(actual: Rect, expected: Rect) {
+      assert_close(actual.x, expected.x);
+      assert_close(actual.y, expected.y);
+      assert_close(actual.w, expected.w);
+      assert_close(actual.h, expected.h);
+   }
+
+   #[test]
+   fn same_aspect_fills_the_window() {
+      let viewport = Viewport::new(1280.0, 720.0);
+
+      assert_rect(viewport.playfield_pixels(), Rect::new(0.0, 0.0, 1280.0, 720.0));
+      assert_rect(viewport.screen_coordinates(), Rect::new(0.0, 0.0, PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT));
+   }
+
+   #[test]
+   fn wider_window_gets_bars_left_and_right() {
+      // 2400x1080 is 20:9, the playfield is 1920 pixels wide at scale 1
+      let viewport = Viewport::new(2400.0, 1080.0);
+
+      assert_rect(viewport.playfield_pixels(), Rect::new(240.0, 0.0, 1920.0, 1080.0));
+      assert_rect(viewport.screen_coordinates(), Rect::new(-240.0, 0.0, 2400.0, 1080.0));
+   }
+
+   #[test]
+   fn taller_window_gets_bars_top_and_bottom() {
+      // 960x960 scales by a half, the playfield is 540 high
+      let viewport = Viewport::new(960.0, 960.0);
+
+      assert_rect(viewport.playfield_pixels(), Rect::new(0.0, 210.0, 960.0, 540.0));
+      assert_rect(viewport.screen_coordinates(), Rect::new(0.0, -420.0, PLAYFIELD_WIDTH, 1920.0));
+   }
+
+   #[test]
+   fn window_points_map_to_the_playfield() {
+      let viewport = Viewport::new(960.0, 960.0);
+
+      assert_eq!(viewport.to_logical(Vec2::new(0.0, 210.0)), Vec2::ZERO);
+      assert_eq!(viewport.to_logical(Vec2::new(960.0, 750.0)), Vec2::new(PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT));
+      // In the bars, outside the playfield
+      assert!(viewport.to_logical(Vec2::new(480.0, 10.0)).y < 0.0);
+   }
+
+   #[test]
+   fn to_logical_undoes_the_letterboxing() {
+      for (width, height) in [(1920.0, 1080.0), (2560.0, 1080.0), (800.0, 600.0), (333.0, 777.0)] {
+         let viewport = Viewport::new(width, height);
+         let playfield = viewport.playfield_pixels();
+
+         for logical in [Vec2::ZERO, Vec2::new(100.0, 900.0), Vec2::new(PLAYFIELD_WIDTH, PLAYFIELD_HEIGHT)] {
+            let pixel = Vec2::new(playfield.x + logical.x / PLAYFIELD_WIDTH * playfield.w,
+                                  playfield.y + logical.y / PLAYFIELD_HEIGHT * playfield.h);
+            let back = viewport.to_logical(pixel);
+
+            assert_close(back.x, logical.x);
+            assert_close(back.y, logical.y);
+         }
+      }
+   }
+
+   #[test]
+   fn empty_window_doesnt_divide_by_zero() {
+      let viewport = Viewport::new(0.0, 0.0);
+      let point = viewport.to_logical(Vec2::new(1.0, 1.0));
+
+      assert!(point.x.is_finite() && point.y.is_finite());
+   }
+}